@@ -0,0 +1,714 @@
+//! `voting-dao`: command-line tool managing the proposals and votes of the voting_dao program.
+
+mod output;
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anchor_lang::AnchorDeserialize;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::hash,
+    instruction::Instruction,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use voting_dao::{LegacyProposal, VotingType};
+use voting_dao_client::{accounts, instructions, pda};
+
+use crate::output::{Format, Output};
+
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Vote receipts counted per `tally_ranked_votes` transaction.
+const RECEIPTS_PER_TRANSACTION: usize = 20;
+
+/// Vote receipts closed per `close_vote_receipts` transaction, each with its voter.
+const CLOSED_RECEIPTS_PER_TRANSACTION: usize = 8;
+
+/// Choices created per `create_proposal` or `add_choices` transaction.
+const CHOICES_PER_TRANSACTION: usize = 8;
+
+#[derive(Parser)]
+#[command(name = "voting-dao", version, about)]
+struct Cli {
+    /// RPC endpoint of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        env = "VOTING_DAO_URL",
+        default_value = "http://localhost:8899"
+    )]
+    url: String,
+
+    /// Keypair file signing and paying for the transactions
+    #[arg(
+        long,
+        short = 'k',
+        env = "VOTING_DAO_KEYPAIR",
+        default_value = "~/.config/solana/id.json"
+    )]
+    keypair: String,
+
+    /// Output format
+    #[arg(long, short = 'o', value_enum, default_value_t = Format::Table)]
+    output: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Voting type of a new proposal
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum VotingKind {
+    /// One choice per voter
+    Single,
+    /// Ranking of the choices, decided by instant-runoff
+    Ranked,
+    /// Any number of approved choices
+    Approval,
+    /// Votes spread over the choices, n votes costing n² credits
+    Quadratic,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a proposal in a realm
+    CreateProposal {
+        /// Address of the realm
+        #[arg(long)]
+        realm: Pubkey,
+        #[arg(long)]
+        title: String,
+        #[command(flatten)]
+        content: ContentArgs,
+        /// Choice of the proposal, repeated for each choice (2 to 32)
+        #[arg(long = "choice", required = true)]
+        choices: Vec<String>,
+        /// Unix timestamp opening the vote, now by default
+        #[arg(long)]
+        start: Option<u64>,
+        /// Unix timestamp closing the vote
+        #[arg(long)]
+        end: u64,
+        /// Quorum raising the one of the realm
+        #[arg(long)]
+        quorum: Option<u64>,
+        /// Approval threshold in percent raising the one of the realm
+        #[arg(long)]
+        threshold: Option<u8>,
+        /// Duration in seconds of the reveal window of secret ballots, public votes by default
+        #[arg(long)]
+        reveal_period: Option<u64>,
+        /// Way voters express their preferences
+        #[arg(long, value_enum, default_value_t = VotingKind::Single)]
+        voting_type: VotingKind,
+        /// Minimum number of approved choices of an approval vote
+        #[arg(long)]
+        min_selections: Option<u8>,
+        /// Maximum number of approved choices of an approval vote
+        #[arg(long)]
+        max_selections: Option<u8>,
+        /// Credit budget of every voter of a quadratic vote, their deposited tokens by default
+        #[arg(long)]
+        credits: Option<u64>,
+    },
+    /// List the proposals of a realm
+    List {
+        /// Address of the realm
+        #[arg(long)]
+        realm: Pubkey,
+    },
+    /// Deposit governing tokens in the vault of a realm, where they weigh the votes of the signer
+    Deposit {
+        /// Address of the realm
+        #[arg(long)]
+        realm: Pubkey,
+        /// Amount of governing tokens
+        amount: u64,
+        /// Governing token account debited, the associated token account of the signer by default
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Withdraw governing tokens from the vault of a realm once the proposals they were counted in have closed
+    Withdraw {
+        /// Address of the realm
+        #[arg(long)]
+        realm: Pubkey,
+        /// Amount of governing tokens
+        amount: u64,
+        /// Governing token account credited, the associated token account of the signer by default
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Show a proposal and its tallies
+    Show {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Vote for a choice of a proposal, rank its choices, approve some of them or spread votes over them
+    Vote {
+        /// Address of the proposal
+        proposal: Pubkey,
+        /// Name of the choice, names of the ranked choices from the preferred one,
+        /// names of the approved choices, or NAME=VOTES for each choice given quadratic votes
+        #[arg(required = true)]
+        choices: Vec<String>,
+    },
+    /// Commit a secret vote for a choice of a proposal using secret ballots, printing the salt needed to reveal it
+    Commit {
+        /// Address of the proposal
+        proposal: Pubkey,
+        /// Name of the choice
+        choice: String,
+        /// Secret salt as 64 hexadecimal digits, random by default
+        #[arg(long, value_parser = parse_salt)]
+        salt: Option<[u8; 32]>,
+    },
+    /// Reveal a committed vote during the reveal window of its proposal
+    Reveal {
+        /// Address of the proposal
+        proposal: Pubkey,
+        /// Name of the committed choice
+        choice: String,
+        /// Salt printed by the commit, as 64 hexadecimal digits
+        #[arg(long, value_parser = parse_salt)]
+        salt: [u8; 32],
+    },
+    /// Count the ranked ballots of an ended proposal until the instant-runoff winner is known
+    Tally {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Cancel a proposal before the end of its vote, as its creator or the admin of its realm
+    Cancel {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Record the results of a finalized or cancelled proposal in an account kept after its deletion
+    Archive {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Show the archived results of a proposal, even once it has been deleted
+    ShowResult {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Delete a proposal closed for at least the deletion delay of its realm
+    Delete {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Close the vote receipts of an ended or deleted proposal, refunding their rent to the accounts that paid it
+    CloseReceipts {
+        /// Address of the proposal
+        proposal: Pubkey,
+    },
+    /// Move a proposal created by the first version of the program to the current layout, as the next proposal of a realm
+    Migrate {
+        /// Address of the proposal in the previous layout
+        proposal: Pubkey,
+        /// Address of the realm the proposal joins
+        #[arg(long)]
+        realm: Pubkey,
+        /// Keypair file of the admin authority of the realm, which accepts the proposal, the signer by default
+        #[arg(long)]
+        authority_keypair: Option<String>,
+        #[command(flatten)]
+        content: ContentArgs,
+    },
+    /// Close the vote receipt of the signer on a proposal of the first version of the program, refunding its rent
+    CloseLegacyReceipt {
+        /// Address of the proposal in the previous layout
+        proposal: Pubkey,
+    },
+}
+
+/// Text body of a proposal, stored off-chain
+#[derive(clap::Args)]
+struct ContentArgs {
+    /// URI where the text body of the proposal is published
+    #[arg(long, default_value = "")]
+    content_uri: String,
+    /// Local copy of the text body, whose SHA-256 hash is recorded with the URI
+    #[arg(long)]
+    content_file: Option<PathBuf>,
+}
+
+impl ContentArgs {
+    /// Returns the URI and the hash of the content, a zero hash without content file.
+    fn load(self) -> Result<(String, [u8; 32])> {
+        let content_hash = match &self.content_file {
+            Some(path) => {
+                let content = std::fs::read(path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                hash(&content).to_bytes()
+            }
+            None => [0; 32],
+        };
+
+        Ok((self.content_uri, content_hash))
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let client = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let output = Output::new(cli.output);
+
+    match cli.command {
+        Command::CreateProposal {
+            realm,
+            title,
+            content,
+            mut choices,
+            start,
+            end,
+            quorum,
+            threshold,
+            reveal_period,
+            voting_type,
+            min_selections,
+            max_selections,
+            credits,
+        } => {
+            if voting_type != VotingKind::Approval
+                && (min_selections.is_some() || max_selections.is_some())
+            {
+                bail!("selection limits only apply to approval votes");
+            }
+            if voting_type != VotingKind::Quadratic && credits.is_some() {
+                bail!("credit budgets only apply to quadratic votes");
+            }
+
+            let signer = load_keypair(&cli.keypair)?;
+            let index = accounts::fetch_realm(&client, &realm)?.proposal_count;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let start = start.unwrap_or(now);
+            // The choices that do not fit in the first transaction are added before the vote opens.
+            if choices.len() > CHOICES_PER_TRANSACTION && start <= now {
+                bail!(
+                    "proposals with more than {CHOICES_PER_TRANSACTION} choices need a start date in the future"
+                );
+            }
+            let (content_uri, content_hash) = content.load()?;
+            let others = choices.split_off(choices.len().min(CHOICES_PER_TRANSACTION));
+
+            let args = voting_dao::instruction::CreateProposal {
+                title,
+                content_uri,
+                content_hash,
+                choices,
+                date_start: start,
+                date_end: end,
+                instructions: vec![],
+                quorum,
+                approval_threshold: threshold,
+                reveal_period,
+                voting_type: match voting_type {
+                    VotingKind::Single => VotingType::SingleChoice,
+                    VotingKind::Ranked => VotingType::Ranked,
+                    VotingKind::Approval => VotingType::Approval {
+                        min_selections,
+                        max_selections,
+                    },
+                    VotingKind::Quadratic => VotingType::Quadratic { credits },
+                },
+            };
+            let proposal = pda::proposal_address(&realm, index);
+            let instruction = instructions::create_proposal(&signer.pubkey(), &realm, index, args);
+            let signature = send(&client, &signer, instruction)?;
+            output.transaction(&signature, Some(&proposal));
+
+            for page in others.chunks(CHOICES_PER_TRANSACTION) {
+                let instruction =
+                    instructions::add_choices(&proposal, &signer.pubkey(), page.to_vec());
+                let signature = send(&client, &signer, instruction)?;
+
+                output.transaction(&signature, None);
+            }
+        }
+        Command::List { realm } => {
+            let proposals = accounts::fetch_realm_proposals(&client, &realm)?;
+
+            output.proposals(&proposals);
+        }
+        Command::Show { proposal } => {
+            let account = accounts::fetch_proposal(&client, &proposal)?;
+            let choices = choice_names(&client, &proposal)?;
+
+            output.proposal(&proposal, &account, &choices);
+        }
+        Command::Deposit {
+            realm,
+            amount,
+            token_account,
+        } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let mint = accounts::fetch_realm(&client, &realm)?.governing_mint;
+            let token_account =
+                token_account.unwrap_or_else(|| associated_token_address(&signer.pubkey(), &mint));
+
+            let instruction = instructions::deposit_governing_tokens(
+                &realm,
+                &mint,
+                &signer.pubkey(),
+                &token_account,
+                amount,
+            );
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::Withdraw {
+            realm,
+            amount,
+            token_account,
+        } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let mint = accounts::fetch_realm(&client, &realm)?.governing_mint;
+            let token_account =
+                token_account.unwrap_or_else(|| associated_token_address(&signer.pubkey(), &mint));
+
+            let instruction = instructions::withdraw_governing_tokens(
+                &realm,
+                &signer.pubkey(),
+                &token_account,
+                amount,
+            );
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::Vote { proposal, choices } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let account = accounts::fetch_proposal(&client, &proposal)?;
+            let names = choice_names(&client, &proposal)?;
+
+            if account.reveal_end.is_some() {
+                bail!("the proposal uses secret ballots, commit the vote then reveal it");
+            }
+
+            let instruction = match account.voting_type {
+                VotingType::SingleChoice => {
+                    let [choice] = choice_indexes(&names, &choices)?[..] else {
+                        bail!("the proposal expects a single choice");
+                    };
+                    instructions::cast_vote(
+                        &proposal,
+                        &signer.pubkey(),
+                        &account.realm,
+                        choice,
+                        &[],
+                    )
+                }
+                VotingType::Ranked => instructions::cast_ranked_vote(
+                    &proposal,
+                    &signer.pubkey(),
+                    &account.realm,
+                    choice_indexes(&names, &choices)?,
+                ),
+                VotingType::Approval { .. } => instructions::cast_approval_vote(
+                    &proposal,
+                    &signer.pubkey(),
+                    &account.realm,
+                    choice_indexes(&names, &choices)?,
+                ),
+                VotingType::Quadratic { .. } => instructions::cast_quadratic_vote(
+                    &proposal,
+                    &signer.pubkey(),
+                    &account.realm,
+                    quadratic_votes(&names, &choices)?,
+                ),
+            };
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::Commit {
+            proposal,
+            choice,
+            salt,
+        } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let account = accounts::fetch_proposal(&client, &proposal)?;
+            if account.reveal_end.is_none() {
+                bail!("the proposal uses public ballots, vote instead");
+            }
+            let names = choice_names(&client, &proposal)?;
+            let choice = choice_indexes(&names, &[choice])?[0];
+            // The secret key of a fresh keypair is 32 bytes drawn from the OS random generator.
+            let salt = salt.unwrap_or_else(|| Keypair::new().secret().to_bytes());
+
+            let commitment = voting_dao::commitment_hash(&signer.pubkey(), choice, &salt);
+            let instruction =
+                instructions::commit_vote(&proposal, &signer.pubkey(), &account.realm, commitment);
+            let signature = send(&client, &signer, instruction)?;
+
+            output.commitment(&signature, &format_salt(&salt));
+        }
+        Command::Reveal {
+            proposal,
+            choice,
+            salt,
+        } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let names = choice_names(&client, &proposal)?;
+            let choice = choice_indexes(&names, &[choice])?[0];
+
+            let instruction = instructions::reveal_vote(&proposal, &signer.pubkey(), choice, salt);
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::Tally { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+
+            loop {
+                let tally = accounts::fetch_proposal(&client, &proposal)?
+                    .ranked_tally
+                    .ok_or_else(|| anyhow!("the proposal does not use ranked ballots"))?;
+                if tally.complete {
+                    break;
+                }
+
+                let pending: Vec<Pubkey> = accounts::fetch_proposal_votes(&client, &proposal)?
+                    .into_iter()
+                    .filter(|(_, vote)| vote.tallied_round < tally.round)
+                    .map(|(address, _)| address)
+                    .collect();
+                if pending.is_empty() && tally.ballots > 0 {
+                    bail!("no ballot left to count in round {}", tally.round);
+                }
+
+                // A proposal without ballots is counted by a transaction without receipts.
+                let pages: Vec<&[Pubkey]> = if pending.is_empty() {
+                    vec![&[]]
+                } else {
+                    pending.chunks(RECEIPTS_PER_TRANSACTION).collect()
+                };
+                for page in pages {
+                    let instruction =
+                        instructions::tally_ranked_votes(&proposal, &signer.pubkey(), page);
+                    let signature = send(&client, &signer, instruction)?;
+
+                    output.transaction(&signature, None);
+                }
+            }
+
+            let account = accounts::fetch_proposal(&client, &proposal)?;
+            let choices = choice_names(&client, &proposal)?;
+            output.proposal(&proposal, &account, &choices);
+        }
+        Command::Cancel { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let realm = accounts::fetch_proposal(&client, &proposal)?.realm;
+            let instruction = instructions::cancel_proposal(&proposal, &realm, &signer.pubkey());
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::Archive { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let instruction = instructions::archive_proposal(&proposal, &signer.pubkey());
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::ShowResult { proposal } => {
+            let address = pda::result_address(&proposal);
+            let result = accounts::fetch_proposal_result(&client, &address)?;
+            let choices = choice_names(&client, &proposal)?;
+
+            output.result(&address, &result, &choices);
+        }
+        Command::Delete { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let realm = accounts::fetch_proposal(&client, &proposal)?.realm;
+            let choices: Vec<Pubkey> = accounts::fetch_proposal_choices(&client, &proposal)?
+                .into_iter()
+                .map(|(address, _)| address)
+                .collect();
+            let instruction =
+                instructions::delete_proposal(&proposal, &realm, &signer.pubkey(), &choices);
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+        Command::CloseReceipts { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let votes = accounts::fetch_proposal_votes(&client, &proposal)?;
+            let Some((_, first)) = votes.first() else {
+                bail!("no vote receipt left to close");
+            };
+            // The proposal may be deleted already, the receipts keep its realm.
+            let realm = first.realm;
+            let receipts: Vec<(Pubkey, Pubkey)> = votes
+                .iter()
+                .map(|(_, vote)| (vote.voter, vote.payer))
+                .collect();
+
+            for page in receipts.chunks(CLOSED_RECEIPTS_PER_TRANSACTION) {
+                let instruction =
+                    instructions::close_vote_receipts(&proposal, &realm, &signer.pubkey(), page);
+                let signature = send(&client, &signer, instruction)?;
+
+                output.transaction(&signature, None);
+            }
+        }
+        Command::Migrate {
+            proposal,
+            realm,
+            authority_keypair,
+            content,
+        } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let authority = authority_keypair.as_deref().map(load_keypair).transpose()?;
+            let data = client.get_account_data(&proposal)?;
+            let legacy = data
+                .get(8..)
+                .and_then(|mut data| LegacyProposal::deserialize(&mut data).ok())
+                .ok_or_else(|| anyhow!("{proposal} is not a proposal in the previous layout"))?;
+            let names: Vec<String> = legacy.votes.into_iter().map(|choice| choice.name).collect();
+            let (content_uri, content_hash) = content.load()?;
+            let index = accounts::fetch_realm(&client, &realm)?.proposal_count;
+
+            let instruction = instructions::migrate_proposal(
+                &proposal,
+                &realm,
+                index,
+                &signer.pubkey(),
+                &authority.as_ref().unwrap_or(&signer).pubkey(),
+                &names,
+                content_uri,
+                content_hash,
+            );
+            let signature = match &authority {
+                Some(authority) => send_signed(&client, &signer, &[authority], instruction)?,
+                None => send(&client, &signer, instruction)?,
+            };
+
+            output.transaction(&signature, Some(&pda::proposal_address(&realm, index)));
+        }
+        Command::CloseLegacyReceipt { proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let instruction = instructions::close_legacy_vote_receipt(&proposal, &signer.pubkey());
+            let signature = send(&client, &signer, instruction)?;
+
+            output.transaction(&signature, None);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a keypair file, expanding a leading `~` to the home directory.
+fn load_keypair(path: &str) -> Result<Keypair> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => format!(
+            "{}/{rest}",
+            std::env::var("HOME").context("HOME is not set")?
+        ),
+        None => path.to_string(),
+    };
+
+    read_keypair_file(&path).map_err(|err| anyhow!("failed to read keypair {path}: {err}"))
+}
+
+/// Sends a transaction made of one instruction, signed and paid by `signer`.
+fn send(client: &RpcClient, signer: &Keypair, instruction: Instruction) -> Result<Signature> {
+    send_signed(client, signer, &[], instruction)
+}
+
+/// Sends a transaction made of one instruction, paid by `signer` and signed by it and `cosigners`.
+fn send_signed(
+    client: &RpcClient,
+    signer: &Keypair,
+    cosigners: &[&Keypair],
+    instruction: Instruction,
+) -> Result<Signature> {
+    let blockhash = client.get_latest_blockhash()?;
+    let mut signers = vec![signer];
+    signers.extend_from_slice(cosigners);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&signer.pubkey()),
+        &signers,
+        blockhash,
+    );
+
+    Ok(client.send_and_confirm_transaction(&transaction)?)
+}
+
+/// Fetches the names of the choices of a proposal, by increasing index.
+fn choice_names(client: &RpcClient, proposal: &Pubkey) -> Result<Vec<String>> {
+    Ok(accounts::fetch_proposal_choices(client, proposal)?
+        .into_iter()
+        .map(|(_, choice)| choice.name)
+        .collect())
+}
+
+/// Returns the indexes of the named choices of a proposal, in the given order.
+fn choice_indexes(choices: &[String], names: &[String]) -> Result<Vec<u8>> {
+    names
+        .iter()
+        .map(|name| {
+            let index = choices.iter().position(|choice| choice == name);
+            index
+                .map(|index| index as u8)
+                .ok_or_else(|| anyhow!("unknown choice {name}"))
+        })
+        .collect()
+}
+
+/// Returns the number of votes of each choice of a proposal from `NAME=VOTES` arguments.
+fn quadratic_votes(choices: &[String], args: &[String]) -> Result<Vec<u64>> {
+    let mut votes = vec![0; choices.len()];
+    for arg in args {
+        let (name, count) = arg
+            .rsplit_once('=')
+            .ok_or_else(|| anyhow!("expected NAME=VOTES, got {arg}"))?;
+        let index = choice_indexes(choices, &[name.to_string()])?[0];
+        votes[index as usize] = count
+            .parse()
+            .with_context(|| format!("invalid number of votes for {name}"))?;
+    }
+
+    Ok(votes)
+}
+
+/// Parses the salt of a secret vote from 64 hexadecimal digits.
+fn parse_salt(hex: &str) -> Result<[u8; 32]> {
+    if hex.len() != 64 || !hex.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        bail!("expected 64 hexadecimal digits");
+    }
+
+    let mut salt = [0; 32];
+    for (byte, digits) in salt.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits)?, 16)?;
+    }
+
+    Ok(salt)
+}
+
+/// Formats the salt of a secret vote as 64 hexadecimal digits, as read by `parse_salt`.
+fn format_salt(salt: &[u8; 32]) -> String {
+    salt.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
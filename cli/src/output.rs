@@ -0,0 +1,283 @@
+//! Printing of the command results as a table or as JSON.
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use voting_dao::{Proposal, ProposalResult};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+pub struct Output {
+    format: Format,
+}
+
+impl Output {
+    pub fn new(format: Format) -> Self {
+        Output { format }
+    }
+
+    /// Prints the signature of a sent transaction and the address of the account it created.
+    pub fn transaction(&self, signature: &Signature, proposal: Option<&Pubkey>) {
+        match self.format {
+            Format::Table => {
+                if let Some(proposal) = proposal {
+                    println!("Proposal:  {proposal}");
+                }
+                println!("Signature: {signature}");
+            }
+            Format::Json => {
+                let mut value = json!({ "signature": signature.to_string() });
+                if let Some(proposal) = proposal {
+                    value["proposal"] = json!(proposal.to_string());
+                }
+                println!("{value}");
+            }
+        }
+    }
+
+    /// Prints the signature of a committed vote and the salt that reveals it.
+    pub fn commitment(&self, signature: &Signature, salt: &str) {
+        match self.format {
+            Format::Table => {
+                println!("Signature: {signature}");
+                println!("Salt:      {salt}");
+            }
+            Format::Json => {
+                println!(
+                    "{}",
+                    json!({ "signature": signature.to_string(), "salt": salt })
+                );
+            }
+        }
+    }
+
+    /// Prints one line per proposal.
+    pub fn proposals(&self, proposals: &[(Pubkey, Proposal)]) {
+        match self.format {
+            Format::Table => {
+                let rows = proposals
+                    .iter()
+                    .map(|(address, proposal)| {
+                        vec![
+                            proposal.index.to_string(),
+                            address.to_string(),
+                            proposal.title.clone(),
+                            format!("{:?}", proposal.state),
+                            proposal.date_end.to_string(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["INDEX", "ADDRESS", "TITLE", "STATE", "END"], rows);
+            }
+            Format::Json => {
+                let values: Vec<Value> = proposals
+                    .iter()
+                    .map(|(address, proposal)| proposal_json(address, proposal, None))
+                    .collect();
+                println!("{}", Value::Array(values));
+            }
+        }
+    }
+
+    /// Prints a proposal followed by the weight and share of each choice, `choices` being their names.
+    pub fn proposal(&self, address: &Pubkey, proposal: &Proposal, choices: &[String]) {
+        match self.format {
+            Format::Table => {
+                println!("Proposal:  {address}");
+                println!("Title:     {}", proposal.title);
+                println!("Content:   {}", proposal.content_uri);
+                println!("State:     {:?}", proposal.state);
+                println!("Type:      {:?}", proposal.voting_type);
+                if let Some(outcome) = proposal.outcome {
+                    println!("Outcome:   {outcome:?}");
+                }
+                println!("Vote:      {} - {}", proposal.date_start, proposal.date_end);
+                if let Some(reveal_end) = proposal.reveal_end {
+                    println!("Reveal:    {} - {reveal_end}", proposal.date_end);
+                }
+                println!(
+                    "Rules:     quorum {}, threshold {}%",
+                    proposal.quorum, proposal.approval_threshold
+                );
+                println!();
+
+                let total = proposal
+                    .votes
+                    .iter()
+                    .fold(0u128, |total, count| total.saturating_add(*count));
+                let rows = proposal
+                    .votes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, count)| {
+                        let share = match total {
+                            0 => 0.0,
+                            total => *count as f64 * 100.0 / total as f64,
+                        };
+                        vec![
+                            choice_name(choices, index),
+                            count.to_string(),
+                            format!("{share:.1}%"),
+                        ]
+                    })
+                    .collect();
+                print_table(&["CHOICE", "WEIGHT", "SHARE"], rows);
+
+                if let Some(tally) = &proposal.ranked_tally {
+                    println!();
+                    println!("Ballots:   {}", tally.ballots);
+                    println!("Round:     {}", tally.round);
+                    for (round, index) in tally.eliminations.iter().enumerate() {
+                        println!(
+                            "Round {}:   {} eliminated",
+                            round + 1,
+                            choice_name(choices, *index as usize)
+                        );
+                    }
+                    if tally.complete {
+                        match tally.winner {
+                            Some(index) => {
+                                println!("Winner:    {}", choice_name(choices, index as usize))
+                            }
+                            None => println!("Winner:    none"),
+                        }
+                    }
+                }
+            }
+            Format::Json => println!("{}", proposal_json(address, proposal, Some(choices))),
+        }
+    }
+
+    /// Prints the archived results of a proposal, `choices` being the names of its choices if it still exists.
+    pub fn result(&self, address: &Pubkey, result: &ProposalResult, choices: &[String]) {
+        match self.format {
+            Format::Table => {
+                println!("Result:    {address}");
+                println!("Proposal:  {}", result.proposal);
+                println!("Realm:     {} #{}", result.realm, result.index);
+                println!("Title:     {}", hex(&result.title_hash));
+                println!("Content:   {}", hex(&result.content_hash));
+                println!("State:     {:?}", result.state);
+                if let Some(outcome) = result.outcome {
+                    println!("Outcome:   {outcome:?}");
+                }
+                if let Some(winner) = result.winner {
+                    println!("Winner:    {}", choice_name(choices, winner as usize));
+                }
+                println!("Vote:      {} - {}", result.date_start, result.date_end);
+                println!("Archived:  {}", result.archived_at);
+                println!();
+
+                let rows = result
+                    .votes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, count)| vec![choice_name(choices, index), count.to_string()])
+                    .collect();
+                print_table(&["CHOICE", "WEIGHT"], rows);
+            }
+            Format::Json => {
+                let value = json!({
+                    "address": address.to_string(),
+                    "proposal": result.proposal.to_string(),
+                    "realm": result.realm.to_string(),
+                    "index": result.index,
+                    "titleHash": hex(&result.title_hash),
+                    "contentHash": hex(&result.content_hash),
+                    "votes": result
+                        .votes
+                        .iter()
+                        .enumerate()
+                        .map(|(index, count)| json!({ "index": index, "count": count.to_string() }))
+                        .collect::<Vec<_>>(),
+                    "winner": result.winner,
+                    "outcome": result.outcome.map(|outcome| format!("{outcome:?}")),
+                    "state": format!("{:?}", result.state),
+                    "dateStart": result.date_start,
+                    "dateEnd": result.date_end,
+                    "archivedAt": result.archived_at,
+                });
+                println!("{value}");
+            }
+        }
+    }
+}
+
+/// Tallies are written as strings, JSON numbers cannot hold every `u128`.
+/// The names of the choices are only written when they were fetched.
+fn proposal_json(address: &Pubkey, proposal: &Proposal, choices: Option<&[String]>) -> Value {
+    json!({
+        "address": address.to_string(),
+        "realm": proposal.realm.to_string(),
+        "index": proposal.index,
+        "title": proposal.title,
+        "contentUri": proposal.content_uri,
+        "contentHash": hex(&proposal.content_hash),
+        "creator": proposal.creator.to_string(),
+        "dateStart": proposal.date_start,
+        "dateEnd": proposal.date_end,
+        "revealEnd": proposal.reveal_end,
+        "votingType": format!("{:?}", proposal.voting_type),
+        "state": format!("{:?}", proposal.state),
+        "outcome": proposal.outcome.map(|outcome| format!("{outcome:?}")),
+        "quorum": proposal.quorum,
+        "approvalThreshold": proposal.approval_threshold,
+        "votes": proposal
+            .votes
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                let mut value = json!({ "index": index, "count": count.to_string() });
+                if let Some(choices) = choices {
+                    value["name"] = json!(choice_name(choices, index));
+                }
+                value
+            })
+            .collect::<Vec<_>>(),
+        "instructions": proposal.instructions.len(),
+        "rankedTally": proposal.ranked_tally.as_ref().map(|tally| json!({
+            "ballots": tally.ballots,
+            "complete": tally.complete,
+            "winner": tally.winner,
+            "round": tally.round,
+            "eliminations": tally.eliminations,
+        })),
+    })
+}
+
+/// Writes a hash in hexadecimal.
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Returns the name of a choice, or its index if its account was not found.
+fn choice_name(choices: &[String], index: usize) -> String {
+    choices
+        .get(index)
+        .cloned()
+        .unwrap_or_else(|| format!("#{index}"))
+}
+
+/// Prints rows under a header, each column padded to its widest cell.
+fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.iter().map(|cell| cell.to_string()).collect();
+    for row in std::iter::once(header).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
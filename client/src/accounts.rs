@@ -0,0 +1,172 @@
+//! Fetchers decoding the voting_dao accounts.
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::Error as RpcError,
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    filter::{Memcmp, RpcFilterType},
+};
+use voting_dao::{ChoiceAccount, Delegation, Proposal, ProposalResult, Realm, VoterRecord, Voting};
+
+use crate::pda::proposal_address;
+
+/// Error returned when fetching a program account.
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<RpcError>),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not owned by the voting_dao program")]
+    InvalidOwner(Pubkey),
+    #[error("account {address} could not be decoded: {source}")]
+    Decode {
+        address: Pubkey,
+        source: anchor_lang::error::Error,
+    },
+}
+
+impl From<RpcError> for FetchError {
+    fn from(error: RpcError) -> Self {
+        FetchError::Rpc(Box::new(error))
+    }
+}
+
+/// Decodes the data of an account, checking its discriminator.
+pub fn decode<T: AccountDeserialize>(address: &Pubkey, mut data: &[u8]) -> Result<T, FetchError> {
+    T::try_deserialize(&mut data).map_err(|source| FetchError::Decode {
+        address: *address,
+        source,
+    })
+}
+
+/// Fetches an account of the program and decodes it.
+pub fn fetch<T: AccountDeserialize>(client: &RpcClient, address: &Pubkey) -> Result<T, FetchError> {
+    let account = client
+        .get_account_with_commitment(address, client.commitment())?
+        .value
+        .ok_or(FetchError::AccountNotFound(*address))?;
+
+    if account.owner != voting_dao::ID {
+        return Err(FetchError::InvalidOwner(*address));
+    }
+
+    decode(address, &account.data)
+}
+
+pub fn fetch_realm(client: &RpcClient, address: &Pubkey) -> Result<Realm, FetchError> {
+    fetch(client, address)
+}
+
+pub fn fetch_proposal(client: &RpcClient, address: &Pubkey) -> Result<Proposal, FetchError> {
+    fetch(client, address)
+}
+
+pub fn fetch_proposal_result(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> Result<ProposalResult, FetchError> {
+    fetch(client, address)
+}
+
+pub fn fetch_voting(client: &RpcClient, address: &Pubkey) -> Result<Voting, FetchError> {
+    fetch(client, address)
+}
+
+pub fn fetch_voter_record(client: &RpcClient, address: &Pubkey) -> Result<VoterRecord, FetchError> {
+    fetch(client, address)
+}
+
+pub fn fetch_delegation(client: &RpcClient, address: &Pubkey) -> Result<Delegation, FetchError> {
+    fetch(client, address)
+}
+
+/// Fetches the proposals of a realm still on chain, with their addresses, by increasing index.
+pub fn fetch_realm_proposals(
+    client: &RpcClient,
+    realm: &Pubkey,
+) -> Result<Vec<(Pubkey, Proposal)>, FetchError> {
+    let proposal_count = fetch_realm(client, realm)?.proposal_count;
+    let addresses: Vec<Pubkey> = (0..proposal_count)
+        .map(|index| proposal_address(realm, index))
+        .collect();
+
+    let mut proposals = Vec::new();
+    // getMultipleAccounts accepts at most 100 addresses per request.
+    for chunk in addresses.chunks(100) {
+        let accounts = client.get_multiple_accounts(chunk)?;
+
+        // Deleted proposals leave a gap in the indexes.
+        for (address, account) in chunk.iter().zip(accounts) {
+            if let Some(account) = account {
+                proposals.push((*address, decode(address, &account.data)?));
+            }
+        }
+    }
+
+    Ok(proposals)
+}
+
+/// Fetches the vote receipts of a proposal with their addresses, for example to count ranked ballots.
+///
+/// The receipts start with an optional choice of variable length, so they are filtered by proposal
+/// once decoded rather than by the RPC node.
+pub fn fetch_proposal_votes(
+    client: &RpcClient,
+    proposal: &Pubkey,
+) -> Result<Vec<(Pubkey, Voting)>, FetchError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            0,
+            Voting::DISCRIMINATOR.to_vec(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut votes = Vec::new();
+    for (address, account) in client.get_program_accounts_with_config(&voting_dao::ID, config)? {
+        let vote: Voting = decode(&address, &account.data)?;
+        if vote.proposal == *proposal {
+            votes.push((address, vote));
+        }
+    }
+
+    Ok(votes)
+}
+
+/// Fetches the choices of a proposal with their addresses, by increasing index.
+///
+/// The choice accounts start with the address of their proposal, so the RPC node filters them.
+pub fn fetch_proposal_choices(
+    client: &RpcClient,
+    proposal: &Pubkey,
+) -> Result<Vec<(Pubkey, ChoiceAccount)>, FetchError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                ChoiceAccount::DISCRIMINATOR.to_vec(),
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, proposal.to_bytes().to_vec())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut choices = Vec::new();
+    for (address, account) in client.get_program_accounts_with_config(&voting_dao::ID, config)? {
+        choices.push((address, decode::<ChoiceAccount>(&address, &account.data)?));
+    }
+    choices.sort_by_key(|(_, choice)| choice.index);
+
+    Ok(choices)
+}
@@ -0,0 +1,640 @@
+//! Builders of the voting_dao instructions.
+//!
+//! Each builder derives the program accounts from their seeds and lists them in the order of the
+//! instruction's accounts structure.
+
+use anchor_lang::{
+    prelude::{pubkey, AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+
+use crate::pda::{
+    choice_address, delegation_address, governance_address, proposal_address, realm_address,
+    result_address, vault_address, vote_address, voter_record_address,
+};
+
+/// SPL Token program, owner of the governing token accounts.
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Builds `create_realm`, `authority` paying for the realm and administrating it.
+#[allow(clippy::too_many_arguments)]
+pub fn create_realm(
+    authority: &Pubkey,
+    governing_mint: &Pubkey,
+    name: &str,
+    quorum: u64,
+    approval_threshold: u8,
+    deletion_delay: u64,
+    min_voting_time: u64,
+    hold_up_time: u64,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeRealm {
+            realm: realm_address(authority, name),
+            governing_mint: *governing_mint,
+            signer: *authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CreateRealm {
+            name: name.to_string(),
+            quorum,
+            approval_threshold,
+            deletion_delay,
+            min_voting_time,
+            hold_up_time,
+        }
+        .data(),
+    }
+}
+
+/// Builds `update_realm`, signed by the current authority of the realm.
+#[allow(clippy::too_many_arguments)]
+pub fn update_realm(
+    signer: &Pubkey,
+    realm: &Pubkey,
+    authority: &Pubkey,
+    quorum: u64,
+    approval_threshold: u8,
+    deletion_delay: u64,
+    min_voting_time: u64,
+    hold_up_time: u64,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::UpdateRealm {
+            realm: *realm,
+            signer: *signer,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::UpdateRealm {
+            authority: *authority,
+            quorum,
+            approval_threshold,
+            deletion_delay,
+            min_voting_time,
+            hold_up_time,
+        }
+        .data(),
+    }
+}
+
+/// Builds `create_proposal` for the proposal number `index` of the realm.
+/// The next index of a realm is its `proposal_count`, the choice accounts are derived from `args.choices`.
+pub fn create_proposal(
+    creator: &Pubkey,
+    realm: &Pubkey,
+    index: u64,
+    args: voting_dao::instruction::CreateProposal,
+) -> Instruction {
+    let proposal = proposal_address(realm, index);
+    let mut accounts = voting_dao::accounts::InitializeProposal {
+        proposal,
+        realm: *realm,
+        signer: *creator,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(choice_accounts(&proposal, &args.choices));
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: args.data(),
+    }
+}
+
+/// Builds `add_choices`, signed by the creator of the proposal before its vote opens.
+pub fn add_choices(proposal: &Pubkey, creator: &Pubkey, choices: Vec<String>) -> Instruction {
+    let mut accounts = voting_dao::accounts::AddChoices {
+        proposal: *proposal,
+        signer: *creator,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(choice_accounts(proposal, &choices));
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::AddChoices { choices }.data(),
+    }
+}
+
+/// Builds `cast_vote` for the choice at index `choice` of a proposal of `realm`, `delegators` being the
+/// (delegation, voter record, vote receipt) of each delegator the voter also votes for.
+pub fn cast_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    realm: &Pubkey,
+    choice: u8,
+    delegators: &[(Pubkey, Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = voting_dao::accounts::InitializeVote {
+        vote: vote_address(proposal, voter),
+        proposal: *proposal,
+        voter_record: voter_record_address(realm, voter),
+        signer: *voter,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+
+    for (delegation, voter_record, receipt) in delegators {
+        accounts.push(AccountMeta::new_readonly(*delegation, false));
+        accounts.push(AccountMeta::new(*voter_record, false));
+        accounts.push(AccountMeta::new(*receipt, false));
+    }
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::CastVote { choice }.data(),
+    }
+}
+
+/// Builds `cast_ranked_vote`, `ranking` listing the indexes of the choices from the preferred one.
+pub fn cast_ranked_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    realm: &Pubkey,
+    ranking: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeVote {
+            vote: vote_address(proposal, voter),
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CastRankedVote { ranking }.data(),
+    }
+}
+
+/// Builds `cast_approval_vote`, `choices` listing the indexes of the approved choices.
+pub fn cast_approval_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    realm: &Pubkey,
+    choices: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeVote {
+            vote: vote_address(proposal, voter),
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CastApprovalVote { choices }.data(),
+    }
+}
+
+/// Builds `cast_quadratic_vote`, `votes` giving the number of votes of each choice.
+pub fn cast_quadratic_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    realm: &Pubkey,
+    votes: Vec<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeVote {
+            vote: vote_address(proposal, voter),
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CastQuadraticVote { votes }.data(),
+    }
+}
+
+/// Builds `commit_vote`, `commitment` being computed with `voting_dao::commitment_hash`.
+pub fn commit_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    realm: &Pubkey,
+    commitment: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeVote {
+            vote: vote_address(proposal, voter),
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CommitVote { commitment }.data(),
+    }
+}
+
+/// Builds `reveal_vote`, with the choice index and salt the commitment was computed from.
+pub fn reveal_vote(proposal: &Pubkey, voter: &Pubkey, choice: u8, salt: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::RevealVote {
+            vote: vote_address(proposal, voter),
+            proposal: *proposal,
+            signer: *voter,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::RevealVote { choice, salt }.data(),
+    }
+}
+
+/// Builds `change_vote`, moving the weight of the voter's receipt to the choice at index `choice`,
+/// `delegators` being the (delegation, vote receipt) of each delegator whose vote moves too.
+pub fn change_vote(
+    proposal: &Pubkey,
+    voter: &Pubkey,
+    choice: u8,
+    delegators: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = voting_dao::accounts::ChangeVote {
+        vote: vote_address(proposal, voter),
+        proposal: *proposal,
+        signer: *voter,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+
+    for (delegation, receipt) in delegators {
+        accounts.push(AccountMeta::new_readonly(*delegation, false));
+        accounts.push(AccountMeta::new(*receipt, false));
+    }
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::ChangeVote { choice }.data(),
+    }
+}
+
+/// Builds `withdraw_vote`, closing the voter's receipt and releasing the deposit it locked in `realm`.
+/// The rent goes back to `payer`, the payer recorded by the receipt: the voter, or the delegate who voted for them.
+pub fn withdraw_vote(
+    proposal: &Pubkey,
+    realm: &Pubkey,
+    voter: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::WithdrawVote {
+            vote: vote_address(proposal, voter),
+            payer: *payer,
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::WithdrawVote {}.data(),
+    }
+}
+
+/// Builds `deposit_governing_tokens`, moving `amount` governing tokens of `owner` from `source` into the vault of the realm.
+pub fn deposit_governing_tokens(
+    realm: &Pubkey,
+    governing_mint: &Pubkey,
+    owner: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::DepositGoverningTokens {
+            voter_record: voter_record_address(realm, owner),
+            vault: vault_address(realm),
+            realm: *realm,
+            governing_mint: *governing_mint,
+            source: *source,
+            signer: *owner,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::DepositGoverningTokens { amount }.data(),
+    }
+}
+
+/// Builds `withdraw_governing_tokens`, moving `amount` governing tokens deposited by `owner` to `destination`.
+pub fn withdraw_governing_tokens(
+    realm: &Pubkey,
+    owner: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::WithdrawGoverningTokens {
+            voter_record: voter_record_address(realm, owner),
+            vault: vault_address(realm),
+            realm: *realm,
+            destination: *destination,
+            signer: *owner,
+            token_program: TOKEN_PROGRAM_ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::WithdrawGoverningTokens { amount }.data(),
+    }
+}
+
+/// Builds `delegate`, giving the voting weight of `owner` in the realm to `delegate`.
+pub fn delegate(realm: &Pubkey, owner: &Pubkey, delegate: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::InitializeDelegation {
+            delegation: delegation_address(realm, owner),
+            realm: *realm,
+            signer: *owner,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::Delegate {
+            delegate: *delegate,
+        }
+        .data(),
+    }
+}
+
+/// Builds `undelegate`, closing the delegation of `owner`.
+pub fn undelegate(realm: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::CloseDelegation {
+            delegation: delegation_address(realm, owner),
+            realm: *realm,
+            signer: *owner,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::Undelegate {}.data(),
+    }
+}
+
+/// Builds `start_proposal`, callable by anyone once the start date of a `Draft` proposal has passed.
+pub fn start_proposal(proposal: &Pubkey, signer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::StartProposal {
+            proposal: *proposal,
+            signer: *signer,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::StartProposal {}.data(),
+    }
+}
+
+/// Builds `finalize_proposal`, callable by anyone once the vote has ended.
+pub fn finalize_proposal(proposal: &Pubkey, signer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::FinalizeProposal {
+            proposal: *proposal,
+            signer: *signer,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::FinalizeProposal {}.data(),
+    }
+}
+
+/// Builds `tally_ranked_votes`, counting the vote receipts `receipts` in the current round.
+pub fn tally_ranked_votes(proposal: &Pubkey, signer: &Pubkey, receipts: &[Pubkey]) -> Instruction {
+    let mut accounts = voting_dao::accounts::TallyRankedVotes {
+        proposal: *proposal,
+        signer: *signer,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        receipts
+            .iter()
+            .map(|receipt| AccountMeta::new(*receipt, false)),
+    );
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::TallyRankedVotes {}.data(),
+    }
+}
+
+/// Builds `execute_proposal`, `remaining_accounts` being the accounts of the stored instructions.
+pub fn execute_proposal(
+    proposal: &Pubkey,
+    realm: &Pubkey,
+    signer: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut accounts = voting_dao::accounts::ExecuteProposal {
+        proposal: *proposal,
+        governance: governance_address(realm),
+        signer: *signer,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(remaining_accounts);
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::ExecuteProposal {}.data(),
+    }
+}
+
+/// Builds `cancel_proposal`, signed by the creator of the proposal or the admin authority of its realm.
+pub fn cancel_proposal(proposal: &Pubkey, realm: &Pubkey, signer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::CancelProposal {
+            proposal: *proposal,
+            realm: *realm,
+            signer: *signer,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CancelProposal {}.data(),
+    }
+}
+
+/// Builds `archive_proposal`, which records the results of a finalized or cancelled proposal.
+/// The signer pays the rent of the result.
+pub fn archive_proposal(proposal: &Pubkey, signer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::ArchiveProposal {
+            result: result_address(proposal),
+            proposal: *proposal,
+            signer: *signer,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::ArchiveProposal {}.data(),
+    }
+}
+
+/// Builds `delete_proposal`, signed by the creator of the proposal.
+/// The `choices` accounts of the proposal are closed with it.
+pub fn delete_proposal(
+    proposal: &Pubkey,
+    realm: &Pubkey,
+    signer: &Pubkey,
+    choices: &[Pubkey],
+) -> Instruction {
+    let mut accounts = voting_dao::accounts::DeleteProposal {
+        proposal: *proposal,
+        realm: *realm,
+        signer: *signer,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        choices
+            .iter()
+            .map(|choice| AccountMeta::new(*choice, false)),
+    );
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::DeleteProposal {}.data(),
+    }
+}
+
+/// Builds `close_vote_receipt`, which refunds the rent of the receipt of `voter` to `payer`, the payer it records,
+/// once the proposal no longer needs it and releases the deposit it locked in `realm`, the realm of the proposal.
+pub fn close_vote_receipt(
+    proposal: &Pubkey,
+    realm: &Pubkey,
+    voter: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::CloseVoteReceipt {
+            vote: vote_address(proposal, voter),
+            payer: *payer,
+            proposal: *proposal,
+            voter_record: voter_record_address(realm, voter),
+            signer: *voter,
+            clock: sysvar::clock::ID,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CloseVoteReceipt {}.data(),
+    }
+}
+
+/// Builds `close_vote_receipts` for the receipts of `receipts`, the (voter, payer) of each receipt,
+/// each refunded to its payer and releasing the deposit it locked in `realm`, the realm of the proposal.
+/// Anyone can sign it.
+pub fn close_vote_receipts(
+    proposal: &Pubkey,
+    realm: &Pubkey,
+    signer: &Pubkey,
+    receipts: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = voting_dao::accounts::CloseVoteReceipts {
+        proposal: *proposal,
+        signer: *signer,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+
+    for (voter, payer) in receipts {
+        accounts.push(AccountMeta::new(vote_address(proposal, voter), false));
+        accounts.push(AccountMeta::new(*payer, false));
+        accounts.push(AccountMeta::new(voter_record_address(realm, voter), false));
+    }
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::CloseVoteReceipts {}.data(),
+    }
+}
+
+/// Builds `migrate_proposal`, which moves the proposal of the first version of the program at `legacy`
+/// to the current layout as the proposal number `index` of `realm`, its next index.
+/// `choices` are the names of the choices of the legacy proposal, in order; the creator signs and pays
+/// the rent of the proposal and of the choice accounts, the admin `authority` of the realm signs too.
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_proposal(
+    legacy: &Pubkey,
+    realm: &Pubkey,
+    index: u64,
+    creator: &Pubkey,
+    authority: &Pubkey,
+    choices: &[String],
+    content_uri: String,
+    content_hash: [u8; 32],
+) -> Instruction {
+    let proposal = proposal_address(realm, index);
+    let mut accounts = voting_dao::accounts::MigrateProposal {
+        legacy: *legacy,
+        proposal,
+        realm: *realm,
+        authority: *authority,
+        signer: *creator,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(choice_accounts(&proposal, choices));
+
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts,
+        data: voting_dao::instruction::MigrateProposal {
+            content_uri,
+            content_hash,
+        }
+        .data(),
+    }
+}
+
+/// Builds `close_legacy_vote_receipt`, which closes the receipt of `voter` on the proposal of the first
+/// version of the program at `legacy` and refunds its rent to them. The voter signs.
+pub fn close_legacy_vote_receipt(legacy: &Pubkey, voter: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: voting_dao::ID,
+        accounts: voting_dao::accounts::CloseLegacyVoteReceipt {
+            vote: vote_address(legacy, voter),
+            proposal: *legacy,
+            signer: *voter,
+        }
+        .to_account_metas(None),
+        data: voting_dao::instruction::CloseLegacyVoteReceipt {}.data(),
+    }
+}
+
+/// Lists the writable choice accounts created for the `choices` of a proposal.
+fn choice_accounts<'a>(
+    proposal: &'a Pubkey,
+    choices: &'a [String],
+) -> impl Iterator<Item = AccountMeta> + 'a {
+    choices
+        .iter()
+        .map(move |name| AccountMeta::new(choice_address(proposal, name), false))
+}
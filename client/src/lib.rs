@@ -0,0 +1,11 @@
+//! Rust client of the voting_dao program.
+//!
+//! - [`pda`] derives the addresses of the program accounts from their seeds.
+//! - [`instructions`] builds the instructions of the program with their accounts in order.
+//! - [`accounts`] fetches program accounts over RPC and decodes them into their typed structures.
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+pub use voting_dao::{self, ID};
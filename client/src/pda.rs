@@ -0,0 +1,72 @@
+//! Program derived addresses of the voting_dao accounts.
+
+use anchor_lang::prelude::Pubkey;
+
+/// Address of the realm named `name` created by `creator`, seeds `["realm", creator, name]`.
+pub fn realm_address(creator: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"realm", creator.as_ref(), name.as_bytes()],
+        &voting_dao::ID,
+    )
+    .0
+}
+
+/// Address of the proposal number `index` of a realm, seeds `["proposal", realm, index]`.
+pub fn proposal_address(realm: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"proposal", realm.as_ref(), &index.to_le_bytes()],
+        &voting_dao::ID,
+    )
+    .0
+}
+
+/// Address of the vote receipt of a voter, seeds `["vote", proposal, voter]`.
+pub fn vote_address(proposal: &Pubkey, voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vote", proposal.as_ref(), voter.as_ref()],
+        &voting_dao::ID,
+    )
+    .0
+}
+
+/// Address of the archived results of a proposal, seeds `["result", proposal]`.
+pub fn result_address(proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"result", proposal.as_ref()], &voting_dao::ID).0
+}
+
+/// Address of the token account holding the governing tokens deposited in a realm, seeds `["vault", realm]`.
+pub fn vault_address(realm: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", realm.as_ref()], &voting_dao::ID).0
+}
+
+/// Address of the record of the tokens deposited by an owner in a realm, seeds `["voter", realm, owner]`.
+pub fn voter_record_address(realm: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"voter", realm.as_ref(), owner.as_ref()], &voting_dao::ID).0
+}
+
+/// Address of the delegation of an owner in a realm, seeds `["delegation", realm, owner]`.
+pub fn delegation_address(realm: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"delegation", realm.as_ref(), owner.as_ref()],
+        &voting_dao::ID,
+    )
+    .0
+}
+
+/// Address signing the instructions of the passed proposals of a realm, seeds `["governance", realm]`.
+pub fn governance_address(realm: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"governance", realm.as_ref()], &voting_dao::ID).0
+}
+
+/// Address of the account of a choice of a proposal, seeds `["choice", proposal, sha256(name)]`.
+pub fn choice_address(proposal: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"choice",
+            proposal.as_ref(),
+            &voting_dao::choice_name_hash(name),
+        ],
+        &voting_dao::ID,
+    )
+    .0
+}
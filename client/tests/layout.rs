@@ -0,0 +1,403 @@
+use anchor_lang::{
+    prelude::Pubkey, AccountSerialize, AnchorDeserialize, AnchorSerialize, Discriminator,
+    InstructionData, Space,
+};
+use voting_dao::{
+    ChoiceAccount, LegacyChoice, LegacyProposal, Proposal, ProposalAccountMeta,
+    ProposalInstruction, ProposalOutcome, ProposalResult, ProposalState, RankedTally, Realm,
+    VoterRecord, Voting, VotingType, DEFAULT_DELETION_DELAY, MAX_CHOICES,
+};
+use voting_dao_client::{
+    accounts::{decode, FetchError},
+    instructions, pda,
+};
+
+fn serialize<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// Proposal filling every field to its maximum length.
+fn largest_proposal() -> Proposal {
+    let meta = ProposalAccountMeta {
+        pubkey: Pubkey::new_unique(),
+        is_signer: true,
+        is_writable: false,
+    };
+    let instruction = ProposalInstruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![meta; 10],
+        data: vec![7; 256],
+    };
+
+    Proposal {
+        title: "t".repeat(128),
+        content_uri: "u".repeat(200),
+        content_hash: [3; 32],
+        votes: (0..MAX_CHOICES as u128).map(|i| u128::MAX - i).collect(),
+        date_start: 1_700_000_000,
+        date_end: 1_700_086_400,
+        reveal_end: Some(1_700_172_800),
+        voting_type: VotingType::Quadratic {
+            credits: Some(u64::MAX),
+        },
+        ranked_tally: Some(RankedTally {
+            ballots: u64::MAX,
+            round: 32,
+            counted: u64::MAX,
+            counts: vec![u128::MAX; MAX_CHOICES],
+            eliminated: u32::MAX >> 1,
+            eliminations: (0..MAX_CHOICES as u8 - 1).rev().collect(),
+            complete: true,
+            winner: Some(0),
+        }),
+        approval_weight: u128::MAX,
+        creator: Pubkey::new_unique(),
+        realm: Pubkey::new_unique(),
+        index: 42,
+        governing_mint: Pubkey::new_unique(),
+        instructions: vec![instruction; 4],
+        quorum: 1_000,
+        approval_threshold: 66,
+        hold_up_time: u64::MAX,
+        outcome: Some(ProposalOutcome::Succeeded),
+        state: ProposalState::Succeeded,
+    }
+}
+
+#[test]
+fn proposal_round_trips_within_its_space() {
+    let proposal = largest_proposal();
+    let data = serialize(&proposal);
+    assert!(data.len() <= 8 + Proposal::INIT_SPACE);
+
+    let address = Pubkey::new_unique();
+    let decoded: Proposal = decode(&address, &data).unwrap();
+    assert_eq!(serialize(&decoded), data);
+
+    assert_eq!(decoded.title, proposal.title);
+    assert_eq!(decoded.content_uri, proposal.content_uri);
+    assert_eq!(decoded.content_hash, [3; 32]);
+    assert_eq!(decoded.votes[31], u128::MAX - 31);
+    assert_eq!(decoded.creator, proposal.creator);
+    assert_eq!(decoded.realm, proposal.realm);
+    assert_eq!(decoded.index, 42);
+    assert_eq!(decoded.reveal_end, Some(1_700_172_800));
+    assert_eq!(decoded.voting_type, proposal.voting_type);
+    let tally = decoded.ranked_tally.unwrap();
+    assert_eq!(tally.eliminations.len(), 31);
+    assert_eq!(tally.winner, Some(0));
+    assert_eq!(decoded.instructions[3].data, vec![7; 256]);
+    assert_eq!(decoded.outcome, Some(ProposalOutcome::Succeeded));
+    assert_eq!(decoded.state, ProposalState::Succeeded);
+}
+
+#[test]
+fn legacy_proposal_converts_to_current_layout() {
+    let legacy = LegacyProposal {
+        description: "Budget annuel".to_string(),
+        title: "Budget".to_string(),
+        votes: vec![
+            LegacyChoice {
+                name: "Pour".to_string(),
+                count: u16::MAX,
+            },
+            LegacyChoice {
+                name: "Contre".to_string(),
+                count: 7,
+            },
+        ],
+        date_start: 1_700_000_000,
+        date_end: 1_700_086_400,
+        creator: Pubkey::new_unique(),
+    };
+    let mut data = Proposal::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert!(data.len() <= 8 + LegacyProposal::INIT_SPACE);
+    assert_eq!(8 + LegacyProposal::INIT_SPACE, 546);
+
+    let realm = Realm {
+        authority: Pubkey::new_unique(),
+        name: "dao".to_string(),
+        governing_mint: Pubkey::new_unique(),
+        quorum: 10,
+        approval_threshold: 60,
+        proposal_count: 3,
+        deletion_delay: DEFAULT_DELETION_DELAY,
+        min_voting_time: 86_400,
+        hold_up_time: 3_600,
+    };
+    let address = Pubkey::new_unique();
+    let proposal = legacy.clone().migrate(
+        address,
+        &realm,
+        "ipfs://budget".to_string(),
+        [1; 32],
+        1_700_000_000,
+    );
+    assert_eq!(proposal.title, "Budget");
+    assert_eq!(proposal.content_uri, "ipfs://budget");
+    assert_eq!(proposal.content_hash, [1; 32]);
+    assert_eq!(proposal.votes, vec![0, 0]);
+    assert_eq!(proposal.voting_type, VotingType::SingleChoice);
+    assert_eq!(proposal.creator, legacy.creator);
+    assert_eq!(proposal.realm, address);
+    assert_eq!(proposal.index, 3);
+    assert_eq!(proposal.governing_mint, realm.governing_mint);
+    assert_eq!(proposal.quorum, 10);
+    assert_eq!(proposal.approval_threshold, 60);
+    assert_eq!(proposal.hold_up_time, 3_600);
+    assert_eq!(proposal.state, ProposalState::Voting);
+
+    let proposal = legacy
+        .clone()
+        .migrate(address, &realm, String::new(), [0; 32], 1_700_086_400);
+    assert_eq!(proposal.votes, vec![u16::MAX as u128, 7]);
+
+    let proposal = legacy.migrate(address, &realm, String::new(), [0; 32], 1_600_000_000);
+    assert_eq!(proposal.state, ProposalState::Draft);
+}
+
+#[test]
+fn choice_account_round_trips_within_its_space() {
+    let choice = ChoiceAccount {
+        proposal: Pubkey::new_unique(),
+        index: 31,
+        name: "c".repeat(64),
+    };
+    let data = serialize(&choice);
+    assert!(data.len() <= 8 + ChoiceAccount::INIT_SPACE);
+
+    let decoded: ChoiceAccount = decode(&Pubkey::new_unique(), &data).unwrap();
+    assert_eq!(serialize(&decoded), data);
+    // fetch_proposal_choices filters the choices of a proposal on the bytes following the discriminator.
+    assert_eq!(&data[8..40], choice.proposal.as_ref());
+}
+
+#[test]
+fn proposal_result_fits_the_space_of_its_choices() {
+    let mut result = ProposalResult {
+        proposal: Pubkey::new_unique(),
+        realm: Pubkey::new_unique(),
+        index: u64::MAX,
+        title_hash: [7; 32],
+        content_hash: [1; 32],
+        votes: vec![u128::MAX; 2],
+        winner: Some(1),
+        outcome: Some(ProposalOutcome::QuorumNotReached),
+        state: ProposalState::Expired,
+        date_start: u64::MAX,
+        date_end: u64::MAX,
+        archived_at: u64::MAX,
+    };
+    assert_eq!(serialize(&result).len(), ProposalResult::space(2));
+
+    result.votes = vec![u128::MAX; MAX_CHOICES];
+    let data = serialize(&result);
+    assert_eq!(data.len(), ProposalResult::space(MAX_CHOICES));
+    assert_eq!(data.len(), 8 + ProposalResult::INIT_SPACE);
+
+    let decoded: ProposalResult = decode(&Pubkey::new_unique(), &data).unwrap();
+    assert_eq!(serialize(&decoded), data);
+}
+
+#[test]
+fn voting_round_trips_within_its_space() {
+    let voting = Voting {
+        choice: Some(4),
+        voter: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        realm: Pubkey::new_unique(),
+        weight: 12,
+        timestamp: 1_700_000_000,
+        commitment: Some([9; 32]),
+        choices: vec![4, 3, 2, 1, 0],
+        tallied_round: 5,
+        votes: vec![u64::MAX; 5],
+        credits_spent: u64::MAX,
+        payer: Pubkey::new_unique(),
+    };
+    let data = serialize(&voting);
+    assert!(data.len() <= 8 + Voting::INIT_SPACE);
+
+    let decoded: Voting = decode(&Pubkey::new_unique(), &data).unwrap();
+    assert_eq!(serialize(&decoded), data);
+    assert_eq!(decoded.choice, voting.choice);
+    assert_eq!(decoded.voter, voting.voter);
+    assert_eq!(decoded.proposal, voting.proposal);
+    assert_eq!(decoded.realm, voting.realm);
+    assert_eq!(decoded.weight, 12);
+    assert_eq!(decoded.commitment, Some([9; 32]));
+    assert_eq!(decoded.choices, vec![4, 3, 2, 1, 0]);
+    assert_eq!(decoded.tallied_round, 5);
+    assert_eq!(decoded.votes, vec![u64::MAX; 5]);
+    assert_eq!(decoded.credits_spent, u64::MAX);
+}
+
+#[test]
+fn realm_round_trips_within_its_space() {
+    let realm = Realm {
+        authority: Pubkey::new_unique(),
+        name: "r".repeat(32),
+        governing_mint: Pubkey::new_unique(),
+        quorum: 5,
+        approval_threshold: 50,
+        proposal_count: 3,
+        deletion_delay: u64::MAX,
+        min_voting_time: u64::MAX,
+        hold_up_time: u64::MAX,
+    };
+    let data = serialize(&realm);
+    assert!(data.len() <= 8 + Realm::INIT_SPACE);
+
+    let decoded: Realm = decode(&Pubkey::new_unique(), &data).unwrap();
+    assert_eq!(serialize(&decoded), data);
+}
+
+#[test]
+fn voter_record_round_trips_within_its_space() {
+    let record = VoterRecord {
+        realm: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        deposited: u64::MAX,
+        active_votes: u64::MAX,
+    };
+    let data = serialize(&record);
+    assert_eq!(data.len(), 8 + VoterRecord::INIT_SPACE);
+
+    let decoded: VoterRecord = decode(&Pubkey::new_unique(), &data).unwrap();
+    assert_eq!(decoded.owner, record.owner);
+    assert_eq!(serialize(&decoded), data);
+}
+
+#[test]
+fn decode_rejects_other_account_type() {
+    let voting = Voting {
+        choice: Some(0),
+        voter: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        realm: Pubkey::new_unique(),
+        weight: 1,
+        timestamp: 0,
+        commitment: None,
+        choices: vec![],
+        tallied_round: 0,
+        votes: vec![],
+        credits_spent: 0,
+        payer: Pubkey::new_unique(),
+    };
+    let data = serialize(&voting);
+
+    let result = decode::<Proposal>(&Pubkey::new_unique(), &data);
+    assert!(matches!(result, Err(FetchError::Decode { .. })));
+}
+
+#[test]
+fn create_proposal_data_round_trips() {
+    let realm = pda::realm_address(&Pubkey::new_unique(), "dao");
+    let creator = Pubkey::new_unique();
+    let args = voting_dao::instruction::CreateProposal {
+        title: "Budget".to_string(),
+        content_uri: "ipfs://budget".to_string(),
+        content_hash: [1; 32],
+        choices: vec!["Pour".to_string(), "Contre".to_string()],
+        date_start: 1,
+        date_end: 2,
+        instructions: vec![],
+        quorum: Some(10),
+        approval_threshold: None,
+        reveal_period: None,
+        voting_type: VotingType::SingleChoice,
+    };
+    let instruction = instructions::create_proposal(&creator, &realm, 3, args);
+
+    assert_eq!(instruction.program_id, voting_dao::ID);
+    assert_eq!(
+        instruction.accounts[0].pubkey,
+        pda::proposal_address(&realm, 3)
+    );
+    assert_eq!(instruction.accounts[1].pubkey, realm);
+    assert_eq!(instruction.accounts[2].pubkey, creator);
+    assert!(instruction.accounts[2].is_signer);
+    let proposal = pda::proposal_address(&realm, 3);
+    let choice_accounts = &instruction.accounts[instruction.accounts.len() - 2..];
+    assert_eq!(
+        choice_accounts[0].pubkey,
+        pda::choice_address(&proposal, "Pour")
+    );
+    assert_eq!(
+        choice_accounts[1].pubkey,
+        pda::choice_address(&proposal, "Contre")
+    );
+    assert!(choice_accounts.iter().all(|meta| meta.is_writable));
+
+    let (discriminator, data) = instruction.data.split_at(8);
+    assert_eq!(
+        discriminator,
+        voting_dao::instruction::CreateProposal::DISCRIMINATOR
+    );
+    let decoded = voting_dao::instruction::CreateProposal::try_from_slice(data).unwrap();
+    assert_eq!(decoded.title, "Budget");
+    assert_eq!(decoded.content_uri, "ipfs://budget");
+    assert_eq!(decoded.choices, vec!["Pour", "Contre"]);
+    assert_eq!(decoded.quorum, Some(10));
+    assert_eq!(decoded.approval_threshold, None);
+    assert_eq!(decoded.voting_type, VotingType::SingleChoice);
+}
+
+#[test]
+fn cast_vote_appends_delegator_accounts() {
+    let proposal = Pubkey::new_unique();
+    let voter = Pubkey::new_unique();
+    let realm = Pubkey::new_unique();
+    let delegator = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    let instruction = instructions::cast_vote(&proposal, &voter, &realm, 0, &[delegator]);
+
+    assert_eq!(
+        instruction.accounts[0].pubkey,
+        pda::vote_address(&proposal, &voter)
+    );
+    assert!(instruction.accounts[0].is_writable);
+    assert_eq!(
+        instruction.accounts[2].pubkey,
+        pda::voter_record_address(&realm, &voter)
+    );
+    assert!(instruction.accounts[2].is_writable);
+    let delegator_accounts = &instruction.accounts[instruction.accounts.len() - 3..];
+    assert_eq!(delegator_accounts[0].pubkey, delegator.0);
+    assert_eq!(delegator_accounts[1].pubkey, delegator.1);
+    assert_eq!(delegator_accounts[2].pubkey, delegator.2);
+    assert!(!delegator_accounts[0].is_writable);
+    assert!(delegator_accounts[1].is_writable);
+    assert!(delegator_accounts[2].is_writable);
+
+    let expected = voting_dao::instruction::CastVote { choice: 0 };
+    assert_eq!(instruction.data, expected.data());
+}
+
+#[test]
+fn tally_ranked_votes_appends_writable_receipts() {
+    let proposal = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let receipts = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+    let instruction = instructions::tally_ranked_votes(&proposal, &signer, &receipts);
+
+    assert_eq!(instruction.accounts[0].pubkey, proposal);
+    let receipt_accounts = &instruction.accounts[instruction.accounts.len() - 2..];
+    assert_eq!(receipt_accounts[0].pubkey, receipts[0]);
+    assert_eq!(receipt_accounts[1].pubkey, receipts[1]);
+    assert!(receipt_accounts
+        .iter()
+        .all(|meta| meta.is_writable && !meta.is_signer));
+    assert_eq!(
+        instruction.data,
+        voting_dao::instruction::TallyRankedVotes {}.data()
+    );
+}
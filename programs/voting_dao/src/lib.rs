@@ -1,216 +1,3192 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Discriminator;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 // This is your program's public key and it will update
 // automatically when you build the project.
 declare_id!("GebXFPNYCQ8Gz1JcAT7BXxxD2Y6gtmzKPJVHu9WsyoKQ");
 
+/// Maximum number of choices of a proposal
+pub const MAX_CHOICES: usize = 32;
+
+/// Deletion delay of the realms created before it was configurable, 30 days in seconds
+pub const DEFAULT_DELETION_DELAY: u64 = 2_592_000;
+
+/// Maximum deletion delay of a realm, 10 years in seconds
+pub const MAX_DELETION_DELAY: u64 = 315_360_000;
+
 #[program]
 mod vote {
     use super::*;
 
+    /// Fonction to create a new realm
+    /// Creates a realm grouping the proposals of a DAO under a shared configuration.
+    /// The signer becomes the admin authority of the realm.
+    /// The realm address is derived from the signer and the name, so a name taken by another creator stays available.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the realm, including the governing mint.
+    /// * `name` - The name of the realm, used with the signer to derive its address.
+    /// * `quorum` - The default minimum total vote weight of the realm's proposals.
+    /// * `approval_threshold` - The default approval threshold of the realm's proposals, between 50 and 99.
+    /// * `deletion_delay` - The time in seconds a proposal must stay closed before it can be deleted,
+    ///   at most `MAX_DELETION_DELAY`.
+    /// * `min_voting_time` - The minimum time in seconds the vote of the realm's proposals must stay open.
+    /// * `hold_up_time` - The time in seconds a proposal waits after its vote closes before its instructions can be executed.
+    /// # Returns
+    /// * `Ok(())` if the realm is created successfully.
+    /// * An error if the name or the voting rules are invalid, or the signer already created a realm with this name.
+    /// # Errors
+    /// * `ProposalError::InvalidRealmName` if the name is empty or longer than 32 bytes.
+    /// * `ProposalError::InvalidThreshold` if the approval threshold is not between 50 and 99.
+    /// * `ProposalError::InvalidDeletionDelay` if the deletion delay exceeds `MAX_DELETION_DELAY`.
+    ///
+    pub fn create_realm(
+        ctx: Context<InitializeRealm>,
+        name: String,
+        quorum: u64,
+        approval_threshold: u8,
+        deletion_delay: u64,
+        min_voting_time: u64,
+        hold_up_time: u64,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= 32,
+            ProposalError::InvalidRealmName
+        );
+
+        require!(
+            (50..=99).contains(&approval_threshold),
+            ProposalError::InvalidThreshold
+        );
+
+        require!(
+            deletion_delay <= MAX_DELETION_DELAY,
+            ProposalError::InvalidDeletionDelay
+        );
+
+        let realm = &mut ctx.accounts.realm;
+
+        realm.authority = ctx.accounts.signer.key();
+        realm.name = name;
+        realm.governing_mint = ctx.accounts.governing_mint.key();
+        realm.quorum = quorum;
+        realm.approval_threshold = approval_threshold;
+        realm.proposal_count = 0;
+        realm.deletion_delay = deletion_delay;
+        realm.min_voting_time = min_voting_time;
+        realm.hold_up_time = hold_up_time;
+
+        emit!(RealmCreated {
+            realm: realm.key(),
+            authority: realm.authority,
+            name: realm.name.clone(),
+            governing_mint: realm.governing_mint,
+            deletion_delay,
+        });
+
+        msg!("Realm created by: {}", realm.authority);
+        msg!("Realm address: {}", ctx.accounts.realm.key());
+
+        Ok(())
+    }
+
+    /// Fonction to update a realm
+    /// Updates the admin authority and the default voting rules of a realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for updating the realm.
+    /// * `authority` - The new admin authority of the realm.
+    /// * `quorum` - The new default minimum total vote weight.
+    /// * `approval_threshold` - The new default approval threshold, between 50 and 99.
+    /// * `deletion_delay` - The new deletion delay in seconds, at most `MAX_DELETION_DELAY`.
+    /// * `min_voting_time` - The new minimum voting time in seconds.
+    /// * `hold_up_time` - The new hold-up time in seconds before the instructions of a proposal can be executed.
+    /// # Returns
+    /// * `Ok(())` if the realm is updated successfully.
+    /// * An error if the signer is not the admin or the voting rules are invalid.
+    /// # Errors
+    /// * `ProposalError::NotRealmAuthority` if the signer is not the admin authority of the realm.
+    /// * `ProposalError::InvalidThreshold` if the approval threshold is not between 50 and 99.
+    /// * `ProposalError::InvalidDeletionDelay` if the deletion delay exceeds `MAX_DELETION_DELAY`.
+    ///
+    /// # Note
+    /// The new voting rules, minimum voting time and hold-up time only apply to proposals created afterwards.
+    /// The new deletion delay applies to every proposal of the realm, including the closed ones.
+    ///
+    pub fn update_realm(
+        ctx: Context<UpdateRealm>,
+        authority: Pubkey,
+        quorum: u64,
+        approval_threshold: u8,
+        deletion_delay: u64,
+        min_voting_time: u64,
+        hold_up_time: u64,
+    ) -> Result<()> {
+        require!(
+            (50..=99).contains(&approval_threshold),
+            ProposalError::InvalidThreshold
+        );
+
+        require!(
+            deletion_delay <= MAX_DELETION_DELAY,
+            ProposalError::InvalidDeletionDelay
+        );
+
+        let realm = &mut ctx.accounts.realm;
+
+        require!(realm.authority == ctx.accounts.signer.key(), ProposalError::NotRealmAuthority);
+
+        realm.authority = authority;
+        realm.quorum = quorum;
+        realm.approval_threshold = approval_threshold;
+        realm.deletion_delay = deletion_delay;
+        realm.min_voting_time = min_voting_time;
+        realm.hold_up_time = hold_up_time;
+
+        emit!(RealmUpdated {
+            realm: realm.key(),
+            authority,
+            quorum,
+            approval_threshold,
+            deletion_delay,
+            min_voting_time,
+            hold_up_time,
+        });
+
+        msg!("Realm updated by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
     /// Fonction to create a new proposal
-    /// Creates a new proposal with the given title, description, choices, start date, and end date.
-    /// The proposal must have between 2 and 5 choices, and the start date must be before the end date.
+    /// Creates a new proposal with the given title, content, choices, start date, and end date.
+    /// The proposal must have between 2 and 32 choices, and the start date must be before the end date.
+    /// The vote must stay open at least the minimum voting time of the realm, counted from the start date
+    /// or from the creation if the start date has passed.
+    /// The text body of the proposal is stored off-chain: the proposal records its URI and its SHA-256 hash.
+    /// Each choice is stored in its own `ChoiceAccount`, more choices can be added with `add_choices`
+    /// until the vote opens.
+    /// The proposal is bound to the governing token mint of its realm: votes are weighted
+    /// by the tokens of that mint the voter has deposited in the vault of the realm.
+    /// The proposal can carry instructions that are executed by the realm's governance authority if the first choice wins.
+    /// The quorum and the approval threshold decide whether the proposal passes once it is finalized,
+    /// they default to the realm's voting rules and can only be raised above them, so a proposal cannot
+    /// pass with less support than the realm requires.
+    /// With a reveal period, the proposal uses secret ballots: votes are committed with `commit_vote`
+    /// until the end date, then revealed with `reveal_vote` during the reveal period.
+    /// With the `Ranked` voting type, voters rank the choices with `cast_ranked_vote` and the winner
+    /// is decided by instant-runoff with `tally_ranked_votes` once the vote has ended.
+    /// With the `Approval` voting type, voters approve any number of choices with `cast_approval_vote`.
+    /// With the `Quadratic` voting type, voters spread votes over the choices with `cast_quadratic_vote`,
+    /// `n` votes on a choice costing `n²` credits of their budget.
+    /// The proposal starts in the `Draft` state, or `Voting` if its start date has already been reached.
+    /// The proposal address is derived from its realm and the realm's proposal counter, so titles can repeat.
     /// # Arguments
-    /// * `ctx` - The context containing the accounts required for the proposal.
-    /// * `title` - The title of the proposal.
-    /// * `description` - A brief description of the proposal.
-    /// * `choices` - A vector of choices for the proposal, must contain between 2 and 5 choices.
+    /// * `ctx` - The context containing the accounts required for the proposal, including the realm.
+    ///   The addresses of the choice accounts, derived from the proposal and the hash of each name,
+    ///   are passed as remaining accounts in the order of the choices.
+    /// * `title` - The title of the proposal, at most 128 bytes.
+    /// * `content_uri` - The URI of the text body of the proposal, at most 200 bytes.
+    /// * `content_hash` - The SHA-256 hash of the text body.
+    /// * `choices` - A vector of choices for the proposal, must contain between 2 and 32 choices with distinct names.
     /// * `date_start` - The start date of the proposal in Unix timestamp format.
     /// * `date_end` - The end date of the proposal in Unix timestamp format.
+    /// * `instructions` - The instructions to execute if the proposal passes, at most 4.
+    /// * `quorum` - The minimum total vote weight required for the proposal to be decided,
+    ///   at least the realm's quorum, or `None` to use the realm's quorum.
+    /// * `approval_threshold` - The percentage of the total weight the winning choice must exceed,
+    ///   between 50 (simple majority) and 99 (for example 66 for a two-thirds supermajority),
+    ///   at least the realm's threshold, or `None` to use the realm's threshold.
+    /// * `reveal_period` - The duration in seconds of the reveal window opening at the end date,
+    ///   or `None` for public votes.
+    /// * `voting_type` - The way voters express their preferences: a single choice, a ranking,
+    ///   a set of approved choices with optional selection limits, or quadratic votes with a credit budget.
     ///
     /// # Returns
     /// * `Ok(())` if the proposal is created successfully.
     /// * An error if the proposal creation fails due to invalid parameters.
     ///
     /// # Errors
-    /// * `ProposalError::InvalidNumberOfChoices` if the number of choices is not between 2 and 5.
+    /// * `ProposalError::InvalidContent` if the title or the content URI is too long.
+    /// * `ProposalError::InvalidNumberOfChoices` if the number of choices is not between 2 and 32.
+    /// * `ProposalError::InvalidChoiceName` if a choice name is empty, longer than 64 bytes or used twice.
+    /// * `ProposalError::InvalidChoiceAccount` if the choice accounts do not match the choice names.
     /// * `ProposalError::DateNotConform` if the start date is not before the end date.
+    /// * `ProposalError::VotingTimeTooShort` if the vote stays open less than the minimum voting time of the realm.
+    /// * `ProposalError::InvalidInstructions` if the instructions exceed the allowed size.
+    /// * `ProposalError::InvalidQuorum` if the quorum is below the realm's quorum.
+    /// * `ProposalError::InvalidThreshold` if the approval threshold is not between 50 and 99
+    ///   or is below the realm's threshold.
+    /// * `ProposalError::InvalidRevealPeriod` if the reveal period is zero or ends after the largest timestamp.
+    /// * `ProposalError::InvalidVotingMode` if a ranked, approval or quadratic proposal has a reveal period, only single choice ballots can be secret.
+    /// * `ProposalError::InvalidSelectionLimits` if the selection limits of an approval proposal are not between 1 and the number of choices,
+    ///   or the minimum is above the maximum.
+    /// * `ProposalError::InvalidCreditBudget` if the fixed credit budget of a quadratic proposal is zero.
     ///
-    pub fn create_proposal(
-        ctx: Context<InitializeProposal>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeProposal<'info>>,
         title: String,
-        description: String,
+        content_uri: String,
+        content_hash: [u8; 32],
         choices: Vec<String>,
         date_start: u64,
         date_end: u64,
+        instructions: Vec<ProposalInstruction>,
+        quorum: Option<u64>,
+        approval_threshold: Option<u8>,
+        reveal_period: Option<u64>,
+        voting_type: VotingType,
     ) -> Result<()> {
+        let quorum = quorum.unwrap_or(ctx.accounts.realm.quorum);
+        let approval_threshold = approval_threshold.unwrap_or(ctx.accounts.realm.approval_threshold);
+
+        require!(
+            title.len() <= 128 && content_uri.len() <= 200,
+            ProposalError::InvalidContent
+        );
+
         require!(
-            choices.len() >= 2 && choices.len() <= 5,
+            choices.len() >= 2 && choices.len() <= MAX_CHOICES,
             ProposalError::InvalidNumberOfChoices
         );
 
         require!(date_start <= date_end, ProposalError::DateNotConform);
 
+        let timestamp = ctx.accounts.clock.unix_timestamp as u64;
+        require!(
+            date_end.saturating_sub(date_start.max(timestamp)) >= ctx.accounts.realm.min_voting_time,
+            ProposalError::VotingTimeTooShort
+        );
+
+        require!(
+            instructions.len() <= 4
+                && instructions
+                    .iter()
+                    .all(|ix| ix.accounts.len() <= 10 && ix.data.len() <= 256),
+            ProposalError::InvalidInstructions
+        );
+
+        require!(
+            quorum >= ctx.accounts.realm.quorum,
+            ProposalError::InvalidQuorum
+        );
+
+        require!(
+            (50..=99).contains(&approval_threshold)
+                && approval_threshold >= ctx.accounts.realm.approval_threshold,
+            ProposalError::InvalidThreshold
+        );
+
+        let reveal_end = match reveal_period {
+            Some(period) => Some(
+                date_end
+                    .checked_add(period)
+                    .filter(|_| period > 0)
+                    .ok_or(ProposalError::InvalidRevealPeriod)?,
+            ),
+            None => None,
+        };
+
+        require!(
+            voting_type == VotingType::SingleChoice || reveal_end.is_none(),
+            ProposalError::InvalidVotingMode
+        );
+
+        if let VotingType::Approval { min_selections, max_selections } = voting_type {
+            let min = min_selections.unwrap_or(1);
+            let max = max_selections.unwrap_or(choices.len() as u8);
+            require!(
+                1 <= min && min <= max && (max as usize) <= choices.len(),
+                ProposalError::InvalidSelectionLimits
+            );
+        }
+
+        if let VotingType::Quadratic { credits } = voting_type {
+            require!(credits != Some(0), ProposalError::InvalidCreditBudget);
+        }
+
         let new_proposal = &mut ctx.accounts.proposal;
 
         new_proposal.creator = ctx.accounts.signer.key();
+        new_proposal.realm = ctx.accounts.realm.key();
+        new_proposal.index = ctx.accounts.realm.proposal_count;
         new_proposal.title = title;
-        new_proposal.description = description;
+        new_proposal.content_uri = content_uri;
+        new_proposal.content_hash = content_hash;
         new_proposal.date_start = date_start;
         new_proposal.date_end = date_end;
+        new_proposal.reveal_end = reveal_end;
+        new_proposal.voting_type = voting_type;
+        new_proposal.ranked_tally = match voting_type {
+            VotingType::Ranked => Some(RankedTally::new(choices.len())),
+            _ => None,
+        };
+        new_proposal.approval_weight = 0;
+        new_proposal.governing_mint = ctx.accounts.realm.governing_mint;
+        new_proposal.instructions = instructions;
+        new_proposal.quorum = quorum;
+        new_proposal.approval_threshold = approval_threshold;
+        new_proposal.hold_up_time = ctx.accounts.realm.hold_up_time;
+        new_proposal.outcome = None;
+        new_proposal.state = if date_start <= timestamp {
+            ProposalState::Voting
+        } else {
+            ProposalState::Draft
+        };
+
+        new_proposal.votes = vec![0; choices.len()];
 
-        new_proposal.votes = choices
-            .into_iter()
-            .map(|name| Choice {
-                name,
-                count: 0,
-            }).collect();
+        ctx.accounts.realm.proposal_count += 1;
 
-        msg!("VotingApp initialized by: {}", new_proposal.creator);
+        emit!(ProposalCreated {
+            proposal: new_proposal.key(),
+            realm: new_proposal.realm,
+            index: new_proposal.index,
+            creator: new_proposal.creator,
+            title: new_proposal.title.clone(),
+            content_uri: new_proposal.content_uri.clone(),
+            content_hash,
+            date_start,
+            date_end,
+            reveal_end,
+            voting_type,
+        });
+
+        create_choice_accounts(
+            ctx.accounts.proposal.key(),
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            0,
+            choices,
+        )?;
+
+        msg!("VotingApp initialized by: {}", ctx.accounts.proposal.creator);
         msg!("Proposal address: {}", ctx.accounts.proposal.key());
 
         Ok(())
     }
 
+    /// Fonction to add choices to a proposal
+    /// Adds choices to a proposal before its vote opens, for proposals with more choices than fit in one transaction.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the choices.
+    ///   The addresses of the new choice accounts, derived from the proposal and the hash of each name,
+    ///   are passed as remaining accounts in the order of the choices.
+    /// * `choices` - The names of the new choices, numbered after the existing ones.
+    /// # Returns
+    /// * `Ok(())` if the choices are added successfully.
+    /// * An error if the signer is not the creator, the vote has opened or a choice is invalid.
+    /// # Errors
+    /// * `ProposalError::NotAuthorized` if the signer is not the creator of the proposal.
+    /// * `ProposalError::ChoicesLocked` if the start date of the proposal has been reached.
+    /// * `ProposalError::InvalidNumberOfChoices` if no choice is given or the proposal would have more than 32 choices.
+    /// * `ProposalError::InvalidChoiceName` if a choice name is empty, longer than 64 bytes or already used in the proposal.
+    /// * `ProposalError::InvalidChoiceAccount` if the choice accounts do not match the choice names.
+    ///
+    /// # Note
+    /// The selection limits of an approval proposal stay valid: they are checked against the initial choices.
+    ///
+    pub fn add_choices<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddChoices<'info>>,
+        choices: Vec<String>,
+    ) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.creator == ctx.accounts.signer.key(), ProposalError::NotAuthorized);
+        require!(
+            proposal.state == ProposalState::Draft && timestamp < proposal.date_start,
+            ProposalError::ChoicesLocked
+        );
+
+        let first_index = proposal.votes.len();
+        let count = first_index + choices.len();
+        require!(
+            !choices.is_empty() && count <= MAX_CHOICES,
+            ProposalError::InvalidNumberOfChoices
+        );
+
+        proposal.votes.resize(count, 0);
+        if let Some(tally) = proposal.ranked_tally.as_mut() {
+            tally.counts.resize(count, 0);
+        }
+
+        create_choice_accounts(
+            proposal.key(),
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            first_index,
+            choices,
+        )
+    }
+
+    /// Fonction to deposit governing tokens
+    /// Moves governing tokens of the signer into the vault of a realm, where they weigh their votes.
+    /// The vault and the voter record of the signer are created by their first deposit.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the deposit, including the source token account.
+    /// * `amount` - The amount of governing tokens to deposit.
+    /// # Returns
+    /// * `Ok(())` if the tokens are deposited successfully.
+    /// * An error if the source token account is invalid or holds too few tokens.
+    /// # Errors
+    /// * `ProposalError::InvalidTokenAccount` if the source is not a governing token account owned by the signer.
+    /// * `ProposalError::TallyOverflow` if the deposit of the signer overflows.
+    ///
+    /// # Note
+    /// The vote instructions read the deposit of the voter rather than a token balance,
+    /// so tokens can no longer be moved to another wallet to vote twice.
+    /// Depositing 0 tokens only creates the voter record.
+    ///
+    pub fn deposit_governing_tokens(ctx: Context<DepositGoverningTokens>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.data_is_empty() {
+            create_vault(ctx.accounts, ctx.bumps.vault)?;
+        }
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.signer.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let record = &mut ctx.accounts.voter_record;
+
+        record.realm = ctx.accounts.realm.key();
+        record.owner = ctx.accounts.signer.key();
+        record.deposited = record
+            .deposited
+            .checked_add(amount)
+            .ok_or(ProposalError::TallyOverflow)?;
+
+        emit!(GoverningTokensDeposited {
+            realm: record.realm,
+            owner: record.owner,
+            amount,
+            deposited: record.deposited,
+        });
+
+        msg!("Governing tokens deposited by: {}", record.owner);
+
+        Ok(())
+    }
+
+    /// Fonction to withdraw governing tokens
+    /// Moves governing tokens deposited by the signer out of the vault of a realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the withdrawal, including the destination token account.
+    /// * `amount` - The amount of governing tokens to withdraw.
+    /// # Returns
+    /// * `Ok(())` if the tokens are withdrawn successfully.
+    /// * An error if the deposit is still locked or too small.
+    /// # Errors
+    /// * `ProposalError::InvalidTokenAccount` if the destination is not a governing token account.
+    /// * `ProposalError::DepositLocked` if a vote receipt of the signer in the realm is still active.
+    /// * `ProposalError::InsufficientDeposit` if the amount exceeds the deposit of the signer.
+    ///
+    /// # Note
+    /// Each vote receipt locks the deposit of its voter, including the receipts created for delegators by their delegate.
+    /// The lock is released when the vote is withdrawn or the receipt is closed, which is possible
+    /// once the proposal is cancelled, finalized, deleted or has ended.
+    ///
+    pub fn withdraw_governing_tokens(
+        ctx: Context<WithdrawGoverningTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.voter_record;
+
+        require!(record.active_votes == 0, ProposalError::DepositLocked);
+        require!(amount <= record.deposited, ProposalError::InsufficientDeposit);
+
+        record.deposited -= amount;
+
+        let realm = ctx.accounts.realm.key();
+        let signer_seeds: &[&[u8]] = &[b"vault", realm.as_ref(), &[ctx.bumps.vault]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(GoverningTokensWithdrawn {
+            realm,
+            owner: record.owner,
+            amount,
+            deposited: record.deposited,
+        });
+
+        msg!("Governing tokens withdrawn by: {}", record.owner);
+
+        Ok(())
+    }
+
+    /// Fonction to delegate voting power
+    /// Delegates the signer's voting power in a realm to a representative.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the delegation, including the realm.
+    /// * `delegate` - The representative voting with the signer's weight.
+    /// # Returns
+    /// * `Ok(())` if the delegation is recorded successfully.
+    /// * An error if the delegate is the signer or a delegation already exists.
+    /// # Errors
+    /// * `ProposalError::InvalidDelegate` if the delegate is the signer.
+    ///
+    /// # Note
+    /// A delegation must be removed with `undelegate` before delegating to another representative.
+    ///
+    pub fn delegate(ctx: Context<InitializeDelegation>, delegate: Pubkey) -> Result<()> {
+        require!(delegate != ctx.accounts.signer.key(), ProposalError::InvalidDelegate);
+
+        let delegation = &mut ctx.accounts.delegation;
+
+        delegation.realm = ctx.accounts.realm.key();
+        delegation.owner = ctx.accounts.signer.key();
+        delegation.delegate = delegate;
+
+        emit!(DelegationCreated {
+            realm: delegation.realm,
+            owner: delegation.owner,
+            delegate,
+        });
+
+        msg!("Voting power of {} delegated to {}", delegation.owner, delegate);
+
+        Ok(())
+    }
+
+    /// Fonction to remove a delegation
+    /// Removes the delegation of the signer's voting power in a realm and refunds its rent.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for removing the delegation.
+    /// # Returns
+    /// * `Ok(())` if the delegation is removed successfully.
+    ///
+    /// # Note
+    /// Votes already cast by the delegate on the signer's behalf are kept, the signer can change or withdraw them.
+    ///
+    pub fn undelegate(ctx: Context<CloseDelegation>) -> Result<()> {
+        let delegation = &ctx.accounts.delegation;
+
+        emit!(DelegationRemoved {
+            realm: delegation.realm,
+            owner: delegation.owner,
+            delegate: delegation.delegate,
+        });
+
+        msg!("Delegation removed by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
     /// Fonction to cast a vote for a proposal
     /// Casts a vote for a specific choice in a proposal.
+    /// The vote is weighted by the governing tokens the voter deposited in the vault of the realm,
+    /// plus the weight delegated to the voter.
     /// # Arguments
-    /// * `ctx` - The context containing the accounts required for voting.
-    /// * `target` - The name of the choice to vote for.
+    /// * `ctx` - The context containing the accounts required for voting, including the voter's record in the realm.
+    ///   Delegated weight is counted by passing, for each delegator, its delegation, its voter record
+    ///   and its vote receipt address as remaining accounts.
+    /// * `choice` - The index of the choice to vote for.
     /// # Returns
     /// * `Ok(())` if the vote is cast successfully.
     /// * An error if the vote cannot be cast due to the proposal being closed or the choice being invalid.
     /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal uses secret, ranked, approval or quadratic ballots.
     /// * `ProposalError::VoteNotOpen` if the proposal is not open for voting.
     /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
     /// * `ProposalError::InvalidChoice` if the choice does not exist in the proposal.
+    /// * `ProposalError::NoVotingWeight` if the voter has deposited no governing tokens and no weight is delegated to them.
+    /// * `ProposalError::InvalidDelegation` if a delegation is not delegated to the voter or its accounts do not match.
+    /// * `ProposalError::InvalidVoterRecord` if the record of a delegator is not its record in the realm of the proposal.
+    /// * `ProposalError::AlreadyVoted` if a delegator has already voted on the proposal.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
     ///
     /// # Note
     /// This function checks the current time against the proposal's start and end dates to determine if voting is allowed.
     /// It also checks if the choice exists in the proposal's list of choices.
-    /// If the choice is valid, it increments the vote count for that choice.
+    /// If the choice is valid, it adds the voter's deposit and the deposits of the delegators to the vote count for that choice.
+    /// The deposits counted stay locked in the vault until the vote receipts are withdrawn or closed.
+    /// The first vote cast on a `Draft` proposal moves it to the `Voting` state.
+    /// The vote receipt records the choice, the voter, the proposal, the weight and the time of the vote.
+    /// A receipt is also created for each delegator with its own weight, so the same weight is never counted twice.
     ///
-    pub fn cast_vote(ctx: Context<InitializeVote>, target: String) -> Result<()> {
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeVote<'info>>,
+        choice: u8,
+    ) -> Result<()> {
         let clock = &ctx.accounts.clock;
         let timestamp = clock.unix_timestamp as u64;
-        let proposal = &mut ctx.accounts.proposal;
+        let weight = lock_deposit(&mut ctx.accounts.voter_record);
+        let proposal = &ctx.accounts.proposal;
 
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        require!(
+            proposal.reveal_end.is_none() && proposal.voting_type == VotingType::SingleChoice,
+            ProposalError::InvalidVotingMode
+        );
         require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
         require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
 
-        let choice = proposal.votes.iter_mut().find(|x| x.name == target);
-        require!(choice.is_some(), ProposalError::InvalidChoice);
+        require!((choice as usize) < proposal.votes.len(), ProposalError::InvalidChoice);
+
+        let delegated_weight = cast_delegated_votes(
+            proposal,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            choice,
+            timestamp,
+        )?;
+        let total_weight = weight
+            .checked_add(delegated_weight)
+            .ok_or(ProposalError::TallyOverflow)?;
+        require!(total_weight > 0, ProposalError::NoVotingWeight);
+
+        let proposal = &mut ctx.accounts.proposal;
+
+        add_votes(&mut proposal.votes[choice as usize], total_weight)?;
+        proposal.state = ProposalState::Voting;
+
+        let vote = &mut ctx.accounts.vote;
+
+        vote.choice = Some(choice);
+        vote.voter = ctx.accounts.signer.key();
+        vote.proposal = ctx.accounts.proposal.key();
+        vote.realm = ctx.accounts.proposal.realm;
+        vote.weight = weight;
+        vote.timestamp = timestamp;
+        vote.commitment = None;
+        vote.choices = Vec::new();
+        vote.tallied_round = 0;
+        vote.votes = Vec::new();
+        vote.credits_spent = 0;
+        vote.payer = ctx.accounts.signer.key();
 
-        choice.unwrap().count += 1;
+        emit!(VoteCast {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            choice,
+            weight,
+            timestamp,
+        });
 
         Ok(())
     }
 
-    /// Fonction to delete a proposal
-    /// Deletes a proposal if it has ended and has been closed for at least 30 days.
+    /// Fonction to cast a ranked vote
+    /// Casts a vote ranking the choices of a ranked proposal by order of preference.
+    /// The vote is weighted by the governing tokens the voter deposited in the vault of the realm.
     /// # Arguments
-    /// * `ctx` - The context containing the accounts required for deleting the proposal.
+    /// * `ctx` - The context containing the accounts required for voting, including the voter's record in the realm.
+    /// * `ranking` - The indexes of the ranked choices, from the preferred one. Choices left out are never supported.
     /// # Returns
-    /// * `Ok(())` if the proposal is deleted successfully.
-    /// * An error if the proposal cannot be deleted due to not being authorized or not meeting the time requirements.
+    /// * `Ok(())` if the vote is cast successfully.
+    /// * An error if the proposal is closed, is not ranked or the ranking is invalid.
     /// # Errors
-    /// * `ProposalError::NotAuthorized` if the signer is not the creator of the proposal.
-    /// * `ProposalError::VoteNotEnded` if the proposal has not ended yet.
-    /// * `ProposalError::TooRecentToDelete` if the proposal was closed less than 30 days ago.
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use ranked ballots.
+    /// * `ProposalError::VoteNotOpen` if the proposal is not open for voting.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::InvalidRanking` if the ranking is empty, repeats a choice or contains an unknown index.
+    /// * `ProposalError::NoVotingWeight` if the voter has deposited no governing tokens.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
     ///
     /// # Note
-    /// This function checks the current time against the proposal's end date to ensure it has ended.
-    /// It also checks if the proposal has been closed for at least 30 days before allowing deletion.
+    /// The weight is added to the first preference, so the running tallies show the first round.
+    /// The receipt records the ranking, and its `choice` is the first preference.
+    /// Delegated weight is not counted in ranked ballots: each member ranks the choices themselves.
+    /// A ranked vote cannot be changed, it can be withdrawn and cast again until the end date.
     ///
-    pub fn delete_proposal(ctx: Context<DeleteProposal>) -> Result<()> {
+    pub fn cast_ranked_vote(ctx: Context<InitializeVote>, ranking: Vec<u8>) -> Result<()> {
         let clock = &ctx.accounts.clock;
         let timestamp = clock.unix_timestamp as u64;
+        let weight = lock_deposit(&mut ctx.accounts.voter_record);
         let proposal = &mut ctx.accounts.proposal;
 
-        require!(proposal.creator == ctx.accounts.signer.key(), ProposalError::NotAuthorized);
-        require!(proposal.date_end < timestamp, ProposalError::VoteNotEnded);
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        require!(proposal.voting_type == VotingType::Ranked, ProposalError::InvalidVotingMode);
+        require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
 
-        const THIRTY_DAYS: u64 = 2_592_000;
         require!(
-            timestamp - proposal.date_end >= THIRTY_DAYS,
-            ProposalError::TooRecentToDelete
+            !ranking.is_empty() && are_distinct_choices(&ranking, proposal.votes.len()),
+            ProposalError::InvalidRanking
         );
+        require!(weight > 0, ProposalError::NoVotingWeight);
 
-        proposal.close(ctx.accounts.signer.to_account_info())?;
-        msg!("Proposal deleted by: {}", ctx.accounts.signer.key());
+        let first = ranking[0] as usize;
+        add_votes(&mut proposal.votes[first], weight)?;
+        proposal.state = ProposalState::Voting;
+        if let Some(tally) = proposal.ranked_tally.as_mut() {
+            tally.ballots = tally.ballots.checked_add(1).ok_or(ProposalError::TallyOverflow)?;
+        }
+
+        let vote = &mut ctx.accounts.vote;
+
+        vote.choice = Some(ranking[0]);
+        vote.voter = ctx.accounts.signer.key();
+        vote.proposal = proposal.key();
+        vote.realm = proposal.realm;
+        vote.weight = weight;
+        vote.timestamp = timestamp;
+        vote.commitment = None;
+        vote.choices = ranking;
+        vote.tallied_round = 0;
+        vote.votes = Vec::new();
+        vote.credits_spent = 0;
+        vote.payer = ctx.accounts.signer.key();
+
+        emit!(RankedVoteCast {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            ranking: vote.choices.clone(),
+            weight,
+            timestamp,
+        });
 
         Ok(())
     }
-}
 
-/// This module contains the account structures and their associated constraints for the voting program.
+    /// Fonction to cast an approval vote
+    /// Casts a vote approving a set of choices of an approval proposal.
+    /// The weight of the voter, the governing tokens they deposited in the vault of the realm, is added to every approved choice.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for voting, including the voter's record in the realm.
+    /// * `choices` - The indexes of the approved choices.
+    /// # Returns
+    /// * `Ok(())` if the vote is cast successfully.
+    /// * An error if the proposal is closed, does not use approval ballots or the selection is invalid.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use approval ballots.
+    /// * `ProposalError::VoteNotOpen` if the proposal is not open for voting.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::InvalidSelection` if the selection repeats a choice, contains an unknown index
+    ///   or is outside the selection limits of the proposal.
+    /// * `ProposalError::NoVotingWeight` if the voter has deposited no governing tokens.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// The weight is counted once in the total weight of the ballots, used for the quorum and the approval threshold,
+    /// however many choices it approves.
+    /// Delegated weight is not counted in approval ballots: each member selects the choices themselves.
+    /// An approval vote cannot be changed, it can be withdrawn and cast again until the end date.
+    ///
+    pub fn cast_approval_vote(ctx: Context<InitializeVote>, choices: Vec<u8>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let weight = lock_deposit(&mut ctx.accounts.voter_record);
+        let proposal = &mut ctx.accounts.proposal;
 
-/// Context for initializing a proposal
-#[derive(Accounts)]
-#[instruction(title: String)]
-pub struct InitializeProposal<'info> {
-    #[account(init, payer = signer, space = 8 + Proposal::INIT_SPACE, seeds = [b"proposal", title.as_bytes()], bump)]
-    pub proposal: Account<'info, Proposal>,
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        let VotingType::Approval { min_selections, max_selections } = proposal.voting_type else {
+            return err!(ProposalError::InvalidVotingMode);
+        };
+        require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
 
-    #[account(mut)]
-    pub signer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let selections = choices.len();
+        require!(
+            selections >= min_selections.unwrap_or(1) as usize
+                && selections <= max_selections.map_or(proposal.votes.len(), |max| max as usize)
+                && are_distinct_choices(&choices, proposal.votes.len()),
+            ProposalError::InvalidSelection
+        );
+        require!(weight > 0, ProposalError::NoVotingWeight);
 
-/// Context for casting a vote
-#[derive(Accounts)]
-pub struct InitializeVote<'info> {
-    #[account(init, space = 8 + Voting::INIT_SPACE, payer=signer, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
-    pub vote: Account<'info, Voting>,
-    #[account(mut)]
-    pub proposal: Account<'info, Proposal>,
+        for choice in &choices {
+            add_votes(&mut proposal.votes[*choice as usize], weight)?;
+        }
+        add_votes(&mut proposal.approval_weight, weight)?;
+        proposal.state = ProposalState::Voting;
 
-    #[account(mut)]
-    pub signer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    pub clock: Sysvar<'info, Clock>,
-}
+        let vote = &mut ctx.accounts.vote;
 
-/// Context for deleting a proposal
-#[derive(Accounts)]
-pub struct DeleteProposal<'info> {
-    #[account(mut, close=signer)]
-    pub proposal: Account<'info, Proposal>,
+        vote.choice = None;
+        vote.voter = ctx.accounts.signer.key();
+        vote.proposal = proposal.key();
+        vote.realm = proposal.realm;
+        vote.weight = weight;
+        vote.timestamp = timestamp;
+        vote.commitment = None;
+        vote.choices = choices;
+        vote.tallied_round = 0;
+        vote.votes = Vec::new();
+        vote.credits_spent = 0;
+        vote.payer = ctx.accounts.signer.key();
 
-    #[account(mut)]
-    pub signer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-    pub clock: Sysvar<'info, Clock>,
-}
+        emit!(ApprovalVoteCast {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            choices: vote.choices.clone(),
+            weight,
+            timestamp,
+        });
 
-/// This module contains the account structures and their associated constraints for the voting program.
+        Ok(())
+    }
 
-/// Structures representing the accounts used in the voting program.
-#[account]
-#[derive(InitSpace)]
-pub struct Proposal {
-    #[max_len(64)]
-    pub description: String,
-    #[max_len(64)]
-    pub title: String,
+    /// Fonction to cast a quadratic vote
+    /// Casts a vote spreading votes over the choices of a quadratic proposal.
+    /// Putting `n` votes on a choice costs `n²` credits, and the total cost must fit in the voter's credit budget:
+    /// the fixed allotment of the proposal, or the governing tokens the voter deposited in the vault of the realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for voting, including the voter's record in the realm.
+    /// * `votes` - The number of votes given to each choice, in the order of the choices.
+    /// # Returns
+    /// * `Ok(())` if the vote is cast successfully.
+    /// * An error if the proposal is closed, does not use quadratic ballots or the votes cost too many credits.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use quadratic ballots.
+    /// * `ProposalError::VoteNotOpen` if the proposal is not open for voting.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::InvalidAllocation` if the votes do not match the choices or are all zero.
+    /// * `ProposalError::NoVotingWeight` if the voter has deposited no governing tokens, even with a fixed allotment.
+    /// * `ProposalError::BudgetExceeded` if the cost of the votes exceeds the voter's credit budget.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// The votes are added to the tallies, which count votes rather than token weight.
+    /// The receipt records the votes given to each choice and the credits spent.
+    /// Delegated weight is not counted in quadratic ballots: each member spends their own budget.
+    /// A quadratic vote cannot be changed, it can be withdrawn and cast again until the end date.
+    ///
+    pub fn cast_quadratic_vote(ctx: Context<InitializeVote>, votes: Vec<u64>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let weight = lock_deposit(&mut ctx.accounts.voter_record);
+        let proposal = &mut ctx.accounts.proposal;
 
-    #[max_len(5)]
-    pub votes: Vec<Choice>,
-    date_start: u64,
-    date_end: u64,
-    creator: Pubkey,
-}
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        let VotingType::Quadratic { credits } = proposal.voting_type else {
+            return err!(ProposalError::InvalidVotingMode);
+        };
+        require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+        require!(
+            votes.len() == proposal.votes.len() && votes.iter().any(|count| *count > 0),
+            ProposalError::InvalidAllocation
+        );
+        require!(weight > 0, ProposalError::NoVotingWeight);
 
-/// Structure representing a choice in a proposal
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
-pub struct Choice {
-    #[max_len(64)]
-    pub name: String,
-    pub count: u16,
+        let budget = credits.unwrap_or(weight);
+        let cost = quadratic_cost(&votes)
+            .filter(|cost| *cost <= budget as u128)
+            .ok_or(ProposalError::BudgetExceeded)?;
+
+        for (tally, count) in proposal.votes.iter_mut().zip(&votes) {
+            add_votes(tally, *count)?;
+        }
+        proposal.state = ProposalState::Voting;
+
+        let vote = &mut ctx.accounts.vote;
+
+        vote.choice = None;
+        vote.voter = ctx.accounts.signer.key();
+        vote.proposal = proposal.key();
+        vote.realm = proposal.realm;
+        vote.weight = weight;
+        vote.timestamp = timestamp;
+        vote.commitment = None;
+        vote.choices = Vec::new();
+        vote.tallied_round = 0;
+        vote.votes = votes;
+        vote.credits_spent = cost as u64;
+        vote.payer = ctx.accounts.signer.key();
+
+        emit!(QuadraticVoteCast {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            votes: vote.votes.clone(),
+            credits_spent: vote.credits_spent,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to commit a secret vote
+    /// Records a hidden vote on a proposal using secret ballots, to be revealed after the end date.
+    /// The vote is weighted by the governing tokens the voter deposited in the vault of the realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for voting, including the voter's record in the realm.
+    /// * `commitment` - The hash of the voter, the choice index and a secret salt, see `commitment_hash`.
+    /// # Returns
+    /// * `Ok(())` if the vote is committed successfully.
+    /// * An error if the proposal is closed or does not use secret ballots.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use secret ballots.
+    /// * `ProposalError::VoteNotOpen` if the proposal is not open for voting.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::NoVotingWeight` if the voter has deposited no governing tokens.
+    ///
+    /// # Note
+    /// The committed weight is not counted until the vote is revealed, so the running tallies stay at zero.
+    /// Delegated weight is not counted in secret ballots: each member commits their own vote.
+    /// A committed vote can be withdrawn and committed again until the end date.
+    ///
+    pub fn commit_vote(ctx: Context<InitializeVote>, commitment: [u8; 32]) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let weight = lock_deposit(&mut ctx.accounts.voter_record);
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        require!(proposal.reveal_end.is_some(), ProposalError::InvalidVotingMode);
+        require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+        require!(weight > 0, ProposalError::NoVotingWeight);
+
+        proposal.state = ProposalState::Voting;
+
+        let vote = &mut ctx.accounts.vote;
+
+        vote.choice = None;
+        vote.voter = ctx.accounts.signer.key();
+        vote.proposal = proposal.key();
+        vote.realm = proposal.realm;
+        vote.weight = weight;
+        vote.timestamp = timestamp;
+        vote.commitment = Some(commitment);
+        vote.choices = Vec::new();
+        vote.tallied_round = 0;
+        vote.votes = Vec::new();
+        vote.credits_spent = 0;
+        vote.payer = ctx.accounts.signer.key();
+
+        emit!(VoteCommitted {
+            proposal: vote.proposal,
+            voter: vote.voter,
+            weight,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to reveal a secret vote
+    /// Checks a committed vote against its choice and salt, and adds its weight to the choice.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for revealing the vote, including the vote receipt.
+    /// * `choice` - The index of the committed choice.
+    /// * `salt` - The secret salt used to compute the commitment.
+    /// # Returns
+    /// * `Ok(())` if the vote is revealed successfully.
+    /// * An error if the reveal window is not open or the choice and salt do not match the commitment.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal has already been finalized.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use secret ballots.
+    /// * `ProposalError::RevealNotOpen` if the current time is not between the end date and the end of the reveal period.
+    /// * `ProposalError::InvalidReveal` if the vote has already been revealed or does not match the commitment.
+    /// * `ProposalError::InvalidChoice` if the choice does not exist in the proposal.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// Commitments that are not revealed before the end of the reveal period are never counted.
+    ///
+    pub fn reveal_vote(ctx: Context<RevealVote>, choice: u8, salt: [u8; 32]) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+        let vote = &mut ctx.accounts.vote;
+
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        let reveal_end = proposal.reveal_end.ok_or(ProposalError::InvalidVotingMode)?;
+        require!(
+            proposal.date_end <= timestamp && timestamp <= reveal_end,
+            ProposalError::RevealNotOpen
+        );
+        require!(
+            vote.commitment == Some(commitment_hash(&vote.voter, choice, &salt)),
+            ProposalError::InvalidReveal
+        );
+        require!((choice as usize) < proposal.votes.len(), ProposalError::InvalidChoice);
+
+        add_votes(&mut proposal.votes[choice as usize], vote.weight)?;
+
+        vote.choice = Some(choice);
+        vote.commitment = None;
+
+        emit!(VoteRevealed {
+            proposal: proposal.key(),
+            voter: vote.voter,
+            choice,
+            weight: vote.weight,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to change a vote
+    /// Moves the weight of an existing vote from its previous choice to a new choice.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for changing the vote, including the vote receipt.
+    ///   The votes cast on behalf of delegators are moved too by passing, for each delegator, its delegation
+    ///   and its vote receipt as remaining accounts.
+    /// * `choice` - The index of the new choice to vote for.
+    /// # Returns
+    /// * `Ok(())` if the vote is changed successfully.
+    /// * An error if the proposal is closed or the choice is invalid.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::InvalidVotingMode` if the proposal uses secret, ranked, approval or quadratic ballots.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::InvalidChoice` if the choice does not exist in the proposal.
+    /// * `ProposalError::InvalidDelegation` if a delegation is not delegated to the voter or its receipt does not match.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// The weight recorded in the receipt is moved as is, the voter's current deposit is not read again.
+    /// The receipts of the delegators that are not passed stay on their previous choice; a delegator
+    /// who removed their delegation can only change their receipt themselves.
+    ///
+    pub fn change_vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ChangeVote<'info>>,
+        choice: u8,
+    ) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+        let vote = &mut ctx.accounts.vote;
+
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        require!(
+            proposal.reveal_end.is_none() && proposal.voting_type == VotingType::SingleChoice,
+            ProposalError::InvalidVotingMode
+        );
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+
+        require!((choice as usize) < proposal.votes.len(), ProposalError::InvalidChoice);
+
+        if let Some(previous_choice) = vote.choice {
+            remove_votes(&mut proposal.votes[previous_choice as usize], vote.weight)?;
+        }
+        add_votes(&mut proposal.votes[choice as usize], vote.weight)?;
+
+        emit!(VoteChanged {
+            proposal: proposal.key(),
+            voter: vote.voter,
+            previous_choice: vote.choice,
+            choice,
+            weight: vote.weight,
+            timestamp,
+        });
+
+        vote.choice = Some(choice);
+        vote.timestamp = timestamp;
+
+        change_delegated_votes(
+            proposal,
+            &ctx.accounts.signer,
+            ctx.remaining_accounts,
+            choice,
+            timestamp,
+        )
+    }
+
+    /// Fonction to withdraw a vote
+    /// Removes the weight of an existing vote from its choice and closes the vote receipt.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for withdrawing the vote, including the vote receipt.
+    /// # Returns
+    /// * `Ok(())` if the vote is withdrawn successfully.
+    /// * An error if the proposal is closed.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    /// * `ProposalError::InvalidVoteReceipt` if the payer is not the one recorded by the receipt.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// The rent of the vote receipt is refunded to its payer, the voter or the delegate who voted for them.
+    /// The voter can vote again afterwards, and the receipt no longer locks their deposit.
+    /// A secret vote is withdrawn before being revealed, so no tally changes.
+    /// A ranked vote is removed from its first preference and from the ballots to count.
+    /// An approval vote is removed from every approved choice and from the total weight of the ballots.
+    /// A quadratic vote is removed from every choice it gave votes to.
+    ///
+    pub fn withdraw_vote(ctx: Context<WithdrawVote>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+        let vote = &ctx.accounts.vote;
+
+        require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+
+        match proposal.voting_type {
+            VotingType::Approval { .. } => {
+                for choice in &vote.choices {
+                    remove_votes(&mut proposal.votes[*choice as usize], vote.weight)?;
+                }
+                remove_votes(&mut proposal.approval_weight, vote.weight)?;
+            }
+            VotingType::Quadratic { .. } => {
+                for (tally, count) in proposal.votes.iter_mut().zip(&vote.votes) {
+                    remove_votes(tally, *count)?;
+                }
+            }
+            _ => {
+                if let Some(choice) = vote.choice {
+                    remove_votes(&mut proposal.votes[choice as usize], vote.weight)?;
+                }
+            }
+        }
+        if let Some(tally) = proposal.ranked_tally.as_mut() {
+            tally.ballots = tally.ballots.checked_sub(1).ok_or(ProposalError::TallyOverflow)?;
+        }
+        release_deposit(&mut ctx.accounts.voter_record);
+
+        emit!(VoteWithdrawn {
+            proposal: proposal.key(),
+            voter: vote.voter,
+            choice: vote.choice,
+            weight: vote.weight,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to start a proposal
+    /// Moves a `Draft` proposal to the `Voting` state once its start date has passed.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for starting the proposal.
+    /// # Returns
+    /// * `Ok(())` if the proposal is started successfully.
+    /// * An error if the proposal is not a draft or its vote is not open.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` state.
+    /// * `ProposalError::VoteNotOpen` if the start date of the proposal has not passed yet.
+    /// * `ProposalError::VoteClosed` if the proposal is closed for voting.
+    ///
+    /// # Note
+    /// Anyone can start a proposal, so that its stored state follows the clock without waiting for a first ballot,
+    /// which also moves it to the `Voting` state.
+    ///
+    pub fn start_proposal(ctx: Context<StartProposal>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.state == ProposalState::Draft, ProposalError::InvalidProposalState);
+        require!(proposal.date_start <= timestamp, ProposalError::VoteNotOpen);
+        require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+
+        proposal.state = ProposalState::Voting;
+
+        emit!(ProposalStarted {
+            proposal: proposal.key(),
+            realm: proposal.realm,
+            index: proposal.index,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to finalize a proposal
+    /// Writes the definitive outcome of a proposal once voting has ended and moves it to its final state.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the finalization.
+    /// # Returns
+    /// * `Ok(())` if the outcome is written successfully.
+    /// * An error if the proposal has not ended or has already been finalized.
+    /// # Errors
+    /// * `ProposalError::VoteNotEnded` if the proposal has not ended yet, including its reveal period.
+    /// * `ProposalError::AlreadyFinalized` if the proposal is no longer in the `Draft` or `Voting` state.
+    /// * `ProposalError::TallyNotComplete` if the instant-runoff count of a ranked proposal is not complete.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// The outcome is `QuorumNotReached` if the total vote weight is below the quorum,
+    /// `Succeeded` if a single choice holds strictly more than the approval threshold of the total weight,
+    /// and `Defeated` otherwise.
+    /// The proposal moves to the `Succeeded`, `Defeated` or `Expired` state accordingly.
+    /// The tallies of a ranked proposal are the ones of the last instant-runoff round.
+    /// The total weight of an approval proposal counts each ballot once, however many choices it approves.
+    /// The tallies of a quadratic proposal count votes, the quorum applies to their total.
+    ///
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.closing_time() < timestamp, ProposalError::VoteNotEnded);
+        require!(proposal.state.is_open(), ProposalError::AlreadyFinalized);
+        require!(proposal.is_tallied(), ProposalError::TallyNotComplete);
+
+        let outcome = proposal.compute_outcome()?;
+        proposal.outcome = Some(outcome);
+        proposal.state = match outcome {
+            ProposalOutcome::Succeeded => ProposalState::Succeeded,
+            ProposalOutcome::Defeated => ProposalState::Defeated,
+            ProposalOutcome::QuorumNotReached => ProposalState::Expired,
+        };
+
+        emit!(ProposalFinalized {
+            proposal: proposal.key(),
+            outcome,
+            state: proposal.state,
+        });
+
+        msg!("Proposal finalized with outcome: {:?}", outcome);
+
+        Ok(())
+    }
+
+    /// Fonction to count ranked votes
+    /// Counts a page of the ranked ballots of a proposal for the current instant-runoff round.
+    /// Each ballot supports its preferred choice that has not been eliminated yet.
+    /// Once every ballot has been counted, the round is closed: a choice holding strictly more than half
+    /// of the counted weight wins, otherwise the choice with the lowest weight is eliminated and the next round starts.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the count.
+    ///   The vote receipts of the page are passed as writable remaining accounts.
+    /// # Returns
+    /// * `Ok(())` if the page is counted successfully.
+    /// * An error if the proposal cannot be counted or a receipt is invalid.
+    /// # Errors
+    /// * `ProposalError::VoteNotEnded` if the proposal has not ended yet.
+    /// * `ProposalError::AlreadyFinalized` if the proposal is finalized or its count is already complete.
+    /// * `ProposalError::InvalidVotingMode` if the proposal does not use ranked ballots.
+    /// * `ProposalError::InvalidBallot` if a receipt belongs to another proposal or has already been counted in the round.
+    /// * `ProposalError::TallyOverflow` if updating a tally of the proposal overflows.
+    ///
+    /// # Note
+    /// Anyone can count the ballots, in as many transactions as needed: every ballot must be counted once per round.
+    /// Ballots whose ranked choices are all eliminated are left out of the following rounds.
+    /// On a tie for the lowest weight, the choice listed last is eliminated.
+    /// When the count completes, the tallies of the proposal are replaced by the ones of the last round.
+    ///
+    pub fn tally_ranked_votes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TallyRankedVotes<'info>>,
+    ) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+        let proposal_key = proposal.key();
+
+        require!(proposal.closing_time() < timestamp, ProposalError::VoteNotEnded);
+        require!(proposal.state.is_open(), ProposalError::AlreadyFinalized);
+
+        let tally = proposal
+            .ranked_tally
+            .as_mut()
+            .ok_or(ProposalError::InvalidVotingMode)?;
+        require!(!tally.complete, ProposalError::AlreadyFinalized);
+
+        if tally.ballots == 0 {
+            close_round(proposal_key, tally)?;
+        }
+
+        for account in ctx.remaining_accounts {
+            require!(!tally.complete, ProposalError::AlreadyFinalized);
+
+            let mut receipt = Account::<Voting>::try_from(account)?;
+            require!(
+                receipt.proposal == proposal_key && receipt.tallied_round < tally.round,
+                ProposalError::InvalidBallot
+            );
+
+            if let Some(choice) = tally.preferred_choice(&receipt.choices) {
+                add_votes(&mut tally.counts[choice as usize], receipt.weight)?;
+            }
+            receipt.tallied_round = tally.round;
+            receipt.exit(&crate::ID)?;
+
+            tally.counted += 1;
+            if tally.counted == tally.ballots {
+                close_round(proposal_key, tally)?;
+            }
+        }
+
+        if tally.complete {
+            proposal.votes = tally.counts.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Fonction to execute a proposal
+    /// Executes the instructions stored in a proposal once it has succeeded and the first choice has won.
+    /// The instructions are signed by the governance authority, a PDA derived from the proposal's realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the execution.
+    ///   Every account used by the stored instructions, including the invoked programs,
+    ///   must be passed as remaining accounts.
+    /// # Returns
+    /// * `Ok(())` if the instructions are executed successfully.
+    /// * An error if the proposal cannot be executed or one of the instructions fails.
+    /// # Errors
+    /// * `ProposalError::AlreadyExecuted` if the proposal is in the `Executed` state.
+    /// * `ProposalError::ProposalNotPassed` if the proposal is not in the `Succeeded` state or the first choice is not the winning choice.
+    /// * `ProposalError::HoldUpNotElapsed` if the hold-up time of the proposal has not elapsed since its vote closed.
+    ///
+    /// # Note
+    /// The proposal must have been finalized beforehand, it moves to the `Executed` state.
+    /// The hold-up time, taken from the realm when the proposal was created, leaves the members time to react to
+    /// a proposal that passed, and the admin authority of the realm time to cancel it.
+    ///
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(
+            proposal.state != ProposalState::Executed,
+            ProposalError::AlreadyExecuted
+        );
+        require!(
+            proposal.state == ProposalState::Succeeded && proposal.winning_choice() == Some(0),
+            ProposalError::ProposalNotPassed
+        );
+        require!(
+            proposal.executable_time() <= ctx.accounts.clock.unix_timestamp as u64,
+            ProposalError::HoldUpNotElapsed
+        );
+
+        proposal.state = ProposalState::Executed;
+
+        let realm = proposal.realm;
+        let signer_seeds: &[&[u8]] = &[
+            b"governance",
+            realm.as_ref(),
+            &[ctx.bumps.governance],
+        ];
+
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.governance.to_account_info());
+
+        for instruction in proposal.instructions.iter() {
+            invoke_signed(&instruction.into(), &account_infos, &[signer_seeds])?;
+        }
+
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            executor: ctx.accounts.signer.key(),
+        });
+
+        msg!("Proposal executed by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    /// Fonction to cancel a proposal
+    /// Cancels a proposal before the end of its vote, for example to withdraw a proposal with a mistake or a malicious payload.
+    /// The admin authority of the realm can also cancel a proposal that succeeded until its hold-up time has elapsed.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the cancellation, including the realm of the proposal.
+    /// # Returns
+    /// * `Ok(())` if the proposal is cancelled successfully.
+    /// * An error if the signer is not allowed to cancel the proposal or its vote has ended.
+    /// # Errors
+    /// * `ProposalError::NotAuthorized` if the signer is neither the creator of the proposal nor the admin authority of its realm.
+    /// * `ProposalError::InvalidProposalState` if the proposal is not in the `Draft` or `Voting` state,
+    ///   or is in the `Succeeded` state and its hold-up time has elapsed.
+    /// * `ProposalError::VoteClosed` if the end date of the proposal has been reached.
+    ///
+    /// # Note
+    /// The proposal moves to the `Cancelled` state: it accepts no more votes and can neither be finalized nor executed.
+    /// Its tallies are kept as they were, it can be deleted like any other proposal.
+    ///
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let signer = ctx.accounts.signer.key();
+        let proposal = &mut ctx.accounts.proposal;
+
+        if proposal.state == ProposalState::Succeeded {
+            require!(ctx.accounts.realm.authority == signer, ProposalError::NotAuthorized);
+            require!(
+                timestamp < proposal.executable_time(),
+                ProposalError::InvalidProposalState
+            );
+        } else {
+            require!(
+                proposal.creator == signer || ctx.accounts.realm.authority == signer,
+                ProposalError::NotAuthorized
+            );
+            require!(proposal.state.is_open(), ProposalError::InvalidProposalState);
+            require!(proposal.date_end > timestamp, ProposalError::VoteClosed);
+        }
+
+        proposal.state = ProposalState::Cancelled;
+
+        emit!(ProposalCancelled {
+            proposal: proposal.key(),
+            realm: proposal.realm,
+            index: proposal.index,
+            canceller: signer,
+        });
+
+        msg!("Proposal cancelled by: {}", signer);
+
+        Ok(())
+    }
+
+    /// Fonction to archive a proposal
+    /// Writes a compact `ProposalResult` recording the results of a proposal that is no longer open,
+    /// so that they remain on chain once the proposal is deleted.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the archival.
+    /// # Returns
+    /// * `Ok(())` if the result is written successfully.
+    /// * An error if the proposal is still open or has already been archived.
+    /// # Errors
+    /// * `ProposalError::InvalidProposalState` if the proposal is in the `Draft` or `Voting` state.
+    ///
+    /// # Note
+    /// Anyone can archive a finalized or cancelled proposal, the signer pays the rent of the result.
+    /// The result records the hashes of the title and of the text body, the final tallies, the state and the outcome,
+    /// the dates of the vote and the time of the archival. The winner is the choice that made the proposal pass,
+    /// if it succeeded. The result is sized to the number of choices of the proposal and cannot be closed.
+    ///
+    pub fn archive_proposal(ctx: Context<ArchiveProposal>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &ctx.accounts.proposal;
+
+        require!(!proposal.state.is_open(), ProposalError::InvalidProposalState);
+
+        let winner = match proposal.outcome {
+            Some(ProposalOutcome::Succeeded) => proposal.winning_choice().map(|index| index as u8),
+            _ => None,
+        };
+
+        let result = &mut ctx.accounts.result;
+        result.proposal = proposal.key();
+        result.realm = proposal.realm;
+        result.index = proposal.index;
+        result.title_hash = hashv(&[proposal.title.as_bytes()]).to_bytes();
+        result.content_hash = proposal.content_hash;
+        result.votes = proposal.votes.clone();
+        result.winner = winner;
+        result.outcome = proposal.outcome;
+        result.state = proposal.state;
+        result.date_start = proposal.date_start;
+        result.date_end = proposal.date_end;
+        result.archived_at = timestamp;
+
+        emit!(ProposalArchived {
+            proposal: proposal.key(),
+            result: result.key(),
+            winner,
+            state: proposal.state,
+        });
+
+        msg!("Proposal archived by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    /// Fonction to delete a proposal
+    /// Deletes a proposal if it has ended and has been closed for at least the deletion delay of its realm.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for deleting the proposal.
+    ///   The choice accounts of the proposal passed as remaining accounts are closed as well.
+    /// # Returns
+    /// * `Ok(())` if the proposal is deleted successfully.
+    /// * An error if the proposal cannot be deleted due to not being authorized or not meeting the time requirements.
+    /// # Errors
+    /// * `ProposalError::NotAuthorized` if the signer is not the creator of the proposal.
+    /// * `ProposalError::VoteNotEnded` if the proposal has not ended yet.
+    /// * `ProposalError::InvalidRealm` if the realm is not the realm of the proposal.
+    /// * `ProposalError::TooRecentToDelete` if the proposal was closed more recently than the deletion delay of its realm.
+    /// * `ProposalError::InvalidChoiceAccount` if a remaining account is not a choice of the proposal.
+    ///
+    /// # Note
+    /// This function checks the current time against the proposal's end date to ensure it has ended.
+    /// It also checks if the proposal has been closed for at least the deletion delay of its realm before allowing deletion.
+    /// A realm with a delay of 0 lets a proposal be deleted as soon as its vote has ended.
+    /// Its results are lost unless it was archived with `archive_proposal` beforehand.
+    ///
+    pub fn delete_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DeleteProposal<'info>>,
+    ) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.creator == ctx.accounts.signer.key(), ProposalError::NotAuthorized);
+        require!(proposal.closing_time() < timestamp, ProposalError::VoteNotEnded);
+
+        require!(
+            timestamp - proposal.closing_time() >= ctx.accounts.realm.deletion_delay,
+            ProposalError::TooRecentToDelete
+        );
+
+        for account in ctx.remaining_accounts {
+            let choice = Account::<ChoiceAccount>::try_from(account)?;
+            require!(choice.proposal == proposal.key(), ProposalError::InvalidChoiceAccount);
+            choice.close(ctx.accounts.signer.to_account_info())?;
+        }
+
+        emit!(ProposalDeleted {
+            proposal: proposal.key(),
+            realm: proposal.realm,
+            index: proposal.index,
+        });
+
+        proposal.close(ctx.accounts.signer.to_account_info())?;
+        msg!("Proposal deleted by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    /// Fonction to close a vote receipt
+    /// Closes the vote receipt of the signer once it is no longer needed and refunds its rent to its payer.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for closing the receipt.
+    /// # Returns
+    /// * `Ok(())` if the receipt is closed successfully.
+    /// * An error if the proposal of the receipt still needs it.
+    /// # Errors
+    /// * `ProposalError::VoteNotEnded` if the proposal is open and has not ended yet, including its reveal period.
+    /// * `ProposalError::TallyNotComplete` if the instant-runoff count of a ranked proposal is not complete.
+    /// * `ProposalError::InvalidVoteReceipt` if the payer is not the one recorded by the receipt.
+    ///
+    /// # Note
+    /// A receipt can be closed once its proposal has been deleted, finalized or cancelled,
+    /// or once its vote has ended and its ranked ballots, if any, have been counted.
+    /// Closing the receipt releases the deposit it locked in the voter record of the signer.
+    /// The rent goes back to the account that paid it: the signer, or the delegate who voted for them.
+    ///
+    pub fn close_vote_receipt(ctx: Context<CloseVoteReceipt>) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+
+        require_receipts_closable(&ctx.accounts.proposal, timestamp)?;
+        release_deposit(&mut ctx.accounts.voter_record);
+
+        emit!(VoteReceiptClosed {
+            proposal: ctx.accounts.proposal.key(),
+            voter: ctx.accounts.signer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Fonction to close vote receipts
+    /// Closes a page of the vote receipts of a proposal once they are no longer needed,
+    /// refunding the rent of each receipt to the account that paid it.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for closing the receipts.
+    ///   The remaining accounts are read by groups of three: a vote receipt of the proposal, the payer it records
+    ///   and the voter's record in the realm of the receipt, all writable.
+    /// # Returns
+    /// * `Ok(())` if the receipts are closed successfully.
+    /// * An error if the proposal still needs its receipts or a receipt is invalid.
+    /// # Errors
+    /// * `ProposalError::VoteNotEnded` if the proposal is open and has not ended yet, including its reveal period.
+    /// * `ProposalError::TallyNotComplete` if the instant-runoff count of a ranked proposal is not complete.
+    /// * `ProposalError::InvalidVoteReceipt` if a receipt belongs to another proposal or is not followed by its payer.
+    /// * `ProposalError::InvalidVoterRecord` if a payer is not followed by the voter's record in the realm of the receipt.
+    ///
+    /// # Note
+    /// Anyone can close the receipts, the rent always goes back to the recorded payers:
+    /// the receipts of delegators are refunded to the delegate who paid for them.
+    /// Each closed receipt releases the deposit it locked.
+    ///
+    pub fn close_vote_receipts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseVoteReceipts<'info>>,
+    ) -> Result<()> {
+        let clock = &ctx.accounts.clock;
+        let timestamp = clock.unix_timestamp as u64;
+        let proposal = ctx.accounts.proposal.key();
+
+        require_receipts_closable(&ctx.accounts.proposal, timestamp)?;
+
+        let groups = ctx.remaining_accounts.chunks_exact(3);
+        require!(groups.remainder().is_empty(), ProposalError::InvalidVoteReceipt);
+
+        for accounts in groups {
+            let receipt = Account::<Voting>::try_from(&accounts[0])?;
+            let payer = &accounts[1];
+            let mut record = Account::<VoterRecord>::try_from(&accounts[2])?;
+            require!(
+                receipt.proposal == proposal && receipt.payer == payer.key(),
+                ProposalError::InvalidVoteReceipt
+            );
+            require!(
+                record.realm == receipt.realm && record.owner == receipt.voter,
+                ProposalError::InvalidVoterRecord
+            );
+
+            release_deposit(&mut record);
+            record.exit(&crate::ID)?;
+            let voter = receipt.voter;
+            receipt.close(payer.clone())?;
+
+            emit!(VoteReceiptClosed { proposal, voter });
+        }
+
+        Ok(())
+    }
+
+    /// Fonction to migrate a proposal
+    /// Rewrites a proposal created by the first version of the program, with `u16` tallies and inline choices,
+    /// in the current layout as the next proposal of a realm: the proposal moves to the address of the next index
+    /// of the realm, the tallies become `u128`, the choices move to `ChoiceAccount`s and the description is replaced
+    /// by a content URI.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for the migration, including the realm the proposal joins
+    ///   and its admin authority. The addresses of the choice accounts, derived from the proposal and the hash of each name,
+    ///   are passed as remaining accounts in the order of the choices.
+    /// * `content_uri` - The URI of the text body of the proposal, at most 200 bytes.
+    /// * `content_hash` - The SHA-256 hash of the text body.
+    /// # Returns
+    /// * `Ok(())` if the proposal is migrated successfully.
+    /// * An error if the account is not a proposal in the previous layout.
+    /// # Errors
+    /// * `ProposalError::AlreadyMigrated` if the legacy account is a proposal in the current layout.
+    /// * `ProposalError::NotAuthorized` if the signer is not the creator of the proposal.
+    /// * `ProposalError::NotRealmAuthority` if the authority is not the admin authority of the realm.
+    /// * `ProposalError::InvalidContent` if the content URI is too long.
+    /// * `ProposalError::InvalidChoiceAccount` if the choice accounts do not match the choice names.
+    ///
+    /// # Note
+    /// Only the creator can migrate a proposal, since they publish the former description at the content URI.
+    /// The admin authority of the realm signs too, since the proposal takes the governing mint and the governance
+    /// authority of the realm.
+    /// The dates are kept. The tallies of an ended vote are kept as they are, counting the wallets that voted;
+    /// the tallies of a vote still open are reset, so that every member votes again weighted by their deposit.
+    /// The proposal takes the governing mint, the quorum and the approval threshold of the realm and the next index of its counter,
+    /// at whose address `[b"proposal", realm, index]` it is written. The legacy account, derived from the title, is closed
+    /// and its rent refunded to the signer, who pays the rent of the proposal and of the choice accounts.
+    /// Votes cast before the migration keep receipts in the previous layout, bound to the legacy address:
+    /// they do not prevent their voters from voting on the migrated proposal.
+    /// Every other instruction fails to read a proposal until it has been migrated.
+    ///
+    pub fn migrate_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateProposal<'info>>,
+        content_uri: String,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        let account = ctx.accounts.legacy.to_account_info();
+
+        let legacy = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == Proposal::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            require!(
+                data.len() == 8 + LegacyProposal::INIT_SPACE,
+                ProposalError::AlreadyMigrated
+            );
+            LegacyProposal::deserialize(&mut &data[8..])?
+        };
+        require!(legacy.creator == ctx.accounts.signer.key(), ProposalError::NotAuthorized);
+        require!(content_uri.len() <= 200, ProposalError::InvalidContent);
+
+        let names = legacy.votes.iter().map(|choice| choice.name.clone()).collect();
+        let timestamp = ctx.accounts.clock.unix_timestamp as u64;
+        let realm = &mut ctx.accounts.realm;
+        let migrated = legacy.migrate(realm.key(), realm, content_uri, content_hash, timestamp);
+        realm.proposal_count += 1;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.set_inner(migrated);
+
+        emit!(ProposalMigrated {
+            proposal: proposal.key(),
+            legacy: account.key(),
+            realm: proposal.realm,
+            index: proposal.index,
+        });
+
+        create_choice_accounts(
+            proposal.key(),
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            0,
+            names,
+        )?;
+        // Closed after the creation of the choices, whose invocations check the lamports.
+        close_account(&account, &ctx.accounts.signer.to_account_info())?;
+
+        msg!("Proposal migrated by: {}", ctx.accounts.signer.key());
+
+        Ok(())
+    }
+
+    /// Fonction to close a legacy vote receipt
+    /// Closes a vote receipt created by the first version of the program and refunds its rent to its voter.
+    /// # Arguments
+    /// * `ctx` - The context containing the accounts required for closing the receipt, including the proposal
+    ///   it was cast on, in the previous layout, which may have been migrated.
+    /// # Returns
+    /// * `Ok(())` if the receipt is closed successfully.
+    /// * An error if the account is not a receipt in the previous layout.
+    /// # Errors
+    /// * `ProposalError::InvalidVoteReceipt` if the receipt uses the current layout.
+    ///
+    /// # Note
+    /// A legacy receipt locks no deposit and no instruction reads it once its proposal is migrated,
+    /// so it can be closed at any time.
+    ///
+    pub fn close_legacy_vote_receipt(ctx: Context<CloseLegacyVoteReceipt>) -> Result<()> {
+        let account = ctx.accounts.vote.to_account_info();
+
+        {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == Voting::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            require!(
+                data.len() == 8 + LegacyVoting::INIT_SPACE,
+                ProposalError::InvalidVoteReceipt
+            );
+        }
+        close_account(&account, &ctx.accounts.signer.to_account_info())?;
+
+        emit!(VoteReceiptClosed {
+            proposal: ctx.accounts.proposal.key(),
+            voter: ctx.accounts.signer.key(),
+        });
+
+        Ok(())
+    }
+}
+
+// This module contains the helpers shared by the instructions of the voting program.
+
+/// Computes the commitment of a secret vote: the SHA-256 hash of the voter, the choice index and the salt.
+/// Binding the voter prevents copying the commitment of another member and revealing it once theirs is revealed.
+pub fn commitment_hash(voter: &Pubkey, choice: u8, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[voter.as_ref(), &[choice], salt]).to_bytes()
+}
+
+/// Computes the seed of the account of a choice: the SHA-256 hash of its name.
+pub fn choice_name_hash(name: &str) -> [u8; 32] {
+    hashv(&[name.as_bytes()]).to_bytes()
+}
+
+/// Closes an account that `Account` cannot read, as Anchor's `close` does: its lamports go to `destination`
+/// and it is handed back to the system program.
+fn close_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    **destination.try_borrow_mut_lamports()? += account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+/// Creates an account owned by `owner` at a program derived address signed by `seeds`, as Anchor's `init` does.
+/// Anyone can send lamports to the address beforehand, so an address already holding lamports is topped up
+/// to the rent-exempt minimum, allocated and assigned instead of being created.
+fn create_pda_account<'info>(
+    payer: &Signer<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space as u64,
+            owner,
+        );
+    }
+
+    let missing = lamports.saturating_sub(account.lamports());
+    if missing > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            missing,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+/// Creates the accounts of new choices of a proposal, numbered from `first_index`.
+/// The remaining accounts are the addresses of the choice accounts, in the order of the names.
+/// The address of a choice is derived from its name, so a name already used in the proposal is rejected.
+fn create_choice_accounts<'info>(
+    proposal: Pubkey,
+    signer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    first_index: usize,
+    names: Vec<String>,
+) -> Result<()> {
+    require!(remaining_accounts.len() == names.len(), ProposalError::InvalidChoiceAccount);
+
+    let space = 8 + ChoiceAccount::INIT_SPACE;
+
+    for (offset, (name, account)) in names.iter().zip(remaining_accounts).enumerate() {
+        require!(!name.is_empty() && name.len() <= 64, ProposalError::InvalidChoiceName);
+
+        let name_hash = choice_name_hash(name);
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"choice", proposal.as_ref(), name_hash.as_ref()],
+            &crate::ID,
+        );
+        require!(account.key() == address, ProposalError::InvalidChoiceAccount);
+        require!(account.data_is_empty(), ProposalError::InvalidChoiceName);
+
+        create_pda_account(
+            signer,
+            account,
+            system_program,
+            space,
+            &crate::ID,
+            &[b"choice", proposal.as_ref(), name_hash.as_ref(), &[bump]],
+        )?;
+
+        let choice = ChoiceAccount {
+            proposal,
+            index: (first_index + offset) as u8,
+            name: name.clone(),
+        };
+        choice.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+
+    emit!(ChoicesAdded {
+        proposal,
+        first_index: first_index as u8,
+        names,
+    });
+
+    Ok(())
+}
+
+/// Returns whether the choice indexes are distinct and all belong to a proposal with `count` choices.
+fn are_distinct_choices(choices: &[u8], count: usize) -> bool {
+    choices
+        .iter()
+        .enumerate()
+        .all(|(i, choice)| (*choice as usize) < count && !choices[..i].contains(choice))
+}
+
+/// Returns the credits cost of quadratic votes, the sum of the squares of the votes given to each choice,
+/// or `None` if it overflows.
+pub fn quadratic_cost(votes: &[u64]) -> Option<u128> {
+    votes.iter().try_fold(0u128, |cost, count| {
+        cost.checked_add((*count as u128).checked_mul(*count as u128)?)
+    })
+}
+
+/// Checks that the vote receipts of a proposal are no longer needed: the proposal has been deleted,
+/// is no longer open, or its vote has ended and its ranked ballots, if any, have been counted.
+fn require_receipts_closable(proposal: &AccountInfo, timestamp: u64) -> Result<()> {
+    // A deleted proposal leaves an empty account behind.
+    if proposal.data_is_empty() {
+        return Ok(());
+    }
+
+    let proposal = Proposal::try_deserialize(&mut &proposal.try_borrow_data()?[..])?;
+    if proposal.state.is_open() {
+        require!(proposal.closing_time() < timestamp, ProposalError::VoteNotEnded);
+        require!(proposal.is_tallied(), ProposalError::TallyNotComplete);
+    }
+
+    Ok(())
+}
+
+/// Adds a weight to a tally of a proposal.
+/// Tallies are `u128` so that they hold the sum of any number of token balances, the addition is still checked.
+fn add_votes(count: &mut u128, weight: u64) -> Result<()> {
+    *count = count
+        .checked_add(weight as u128)
+        .ok_or(ProposalError::TallyOverflow)?;
+    Ok(())
+}
+
+/// Removes from a tally of a proposal a weight that was previously added to it.
+fn remove_votes(count: &mut u128, weight: u64) -> Result<()> {
+    *count = count
+        .checked_sub(weight as u128)
+        .ok_or(ProposalError::TallyOverflow)?;
+    Ok(())
+}
+
+/// Closes the current instant-runoff round and emits its result.
+fn close_round(proposal: Pubkey, tally: &mut RankedTally) -> Result<()> {
+    let round = tally.round;
+    let counts = tally.close_round()?;
+
+    emit!(RankedRoundTallied {
+        proposal,
+        round,
+        counts,
+        eliminated: if tally.complete { None } else { tally.eliminations.last().copied() },
+        winner: tally.winner,
+    });
+
+    Ok(())
+}
+
+/// Locks the deposit of a voter for a new vote receipt and returns it as their voting weight.
+fn lock_deposit(record: &mut VoterRecord) -> u64 {
+    record.active_votes += 1;
+    record.deposited
+}
+
+/// Releases the deposit counted by a vote receipt that is withdrawn or closed.
+fn release_deposit(record: &mut VoterRecord) {
+    record.active_votes = record.active_votes.saturating_sub(1);
+}
+
+/// Casts the votes of the delegators passed as remaining accounts on behalf of their delegate.
+/// The remaining accounts are read by groups of three: the delegation, the delegator's voter record, whose deposit
+/// is locked, and the delegator's vote receipt, which is created here. Returns the total delegated weight.
+fn cast_delegated_votes<'info>(
+    proposal: &Account<'info, Proposal>,
+    signer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    choice: u8,
+    timestamp: u64,
+) -> Result<u64> {
+    let groups = remaining_accounts.chunks_exact(3);
+    require!(groups.remainder().is_empty(), ProposalError::InvalidDelegation);
+
+    let space = 8 + Voting::INIT_SPACE;
+    let mut delegated_weight = 0;
+
+    for accounts in groups {
+        let delegation = Account::<Delegation>::try_from(&accounts[0])?;
+        let mut record = Account::<VoterRecord>::try_from(&accounts[1])?;
+        let receipt = &accounts[2];
+
+        require!(
+            delegation.realm == proposal.realm && delegation.delegate == signer.key(),
+            ProposalError::InvalidDelegation
+        );
+        require!(
+            record.realm == proposal.realm && record.owner == delegation.owner,
+            ProposalError::InvalidVoterRecord
+        );
+        let weight = lock_deposit(&mut record);
+        record.exit(&crate::ID)?;
+
+        let proposal_key = proposal.key();
+        let (receipt_key, bump) = Pubkey::find_program_address(
+            &[b"vote", proposal_key.as_ref(), delegation.owner.as_ref()],
+            &crate::ID,
+        );
+        require!(receipt.key() == receipt_key, ProposalError::InvalidDelegation);
+        require!(receipt.data_is_empty(), ProposalError::AlreadyVoted);
+
+        create_pda_account(
+            signer,
+            receipt,
+            system_program,
+            space,
+            &crate::ID,
+            &[b"vote", proposal_key.as_ref(), delegation.owner.as_ref(), &[bump]],
+        )?;
+
+        let vote = Voting {
+            choice: Some(choice),
+            voter: delegation.owner,
+            proposal: proposal_key,
+            realm: proposal.realm,
+            weight,
+            timestamp,
+            commitment: None,
+            choices: Vec::new(),
+            tallied_round: 0,
+            votes: Vec::new(),
+            credits_spent: 0,
+            payer: signer.key(),
+        };
+        vote.try_serialize(&mut &mut receipt.try_borrow_mut_data()?[..])?;
+
+        emit!(VoteCast {
+            proposal: proposal_key,
+            voter: delegation.owner,
+            choice,
+            weight: vote.weight,
+            timestamp,
+        });
+
+        delegated_weight = weight
+            .checked_add(delegated_weight)
+            .ok_or(ProposalError::TallyOverflow)?;
+    }
+
+    Ok(delegated_weight)
+}
+
+/// Moves the votes cast on behalf of the delegators passed as remaining accounts to the new choice of their delegate.
+/// The remaining accounts are read by groups of two: the delegation and the delegator's vote receipt, writable.
+fn change_delegated_votes<'info>(
+    proposal: &mut Account<'info, Proposal>,
+    signer: &Signer<'info>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    choice: u8,
+    timestamp: u64,
+) -> Result<()> {
+    let groups = remaining_accounts.chunks_exact(2);
+    require!(groups.remainder().is_empty(), ProposalError::InvalidDelegation);
+
+    let proposal_key = proposal.key();
+
+    for accounts in groups {
+        let delegation = Account::<Delegation>::try_from(&accounts[0])?;
+        let mut vote = Account::<Voting>::try_from(&accounts[1])?;
+
+        require!(
+            delegation.realm == proposal.realm && delegation.delegate == signer.key(),
+            ProposalError::InvalidDelegation
+        );
+        let (receipt_key, _) = Pubkey::find_program_address(
+            &[b"vote", proposal_key.as_ref(), delegation.owner.as_ref()],
+            &crate::ID,
+        );
+        require!(vote.key() == receipt_key, ProposalError::InvalidDelegation);
+
+        if let Some(previous_choice) = vote.choice {
+            remove_votes(&mut proposal.votes[previous_choice as usize], vote.weight)?;
+        }
+        add_votes(&mut proposal.votes[choice as usize], vote.weight)?;
+
+        emit!(VoteChanged {
+            proposal: proposal_key,
+            voter: vote.voter,
+            previous_choice: vote.choice,
+            choice,
+            weight: vote.weight,
+            timestamp,
+        });
+
+        vote.choice = Some(choice);
+        vote.timestamp = timestamp;
+        vote.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+// This module contains the account structures and their associated constraints for the voting program.
+
+/// Context for initializing a realm
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct InitializeRealm<'info> {
+    #[account(init, payer = signer, space = 8 + Realm::INIT_SPACE, seeds = [b"realm", signer.key().as_ref(), name.as_bytes()], bump)]
+    pub realm: Account<'info, Realm>,
+    pub governing_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for updating a realm
+#[derive(Accounts)]
+pub struct UpdateRealm<'info> {
+    #[account(mut)]
+    pub realm: Account<'info, Realm>,
+
+    pub signer: Signer<'info>,
+}
+
+/// Context for initializing a proposal
+#[derive(Accounts)]
+pub struct InitializeProposal<'info> {
+    #[account(init, payer = signer, space = 8 + Proposal::INIT_SPACE, seeds = [b"proposal", realm.key().as_ref(), realm.proposal_count.to_le_bytes().as_ref()], bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for adding choices to a proposal
+#[derive(Accounts)]
+pub struct AddChoices<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Creates the token account holding the governing tokens deposited in a realm.
+/// The vault is its own authority, so only the program can move the tokens out.
+fn create_vault(accounts: &DepositGoverningTokens, bump: u8) -> Result<()> {
+    let realm = accounts.realm.key();
+
+    create_pda_account(
+        &accounts.signer,
+        &accounts.vault.to_account_info(),
+        &accounts.system_program,
+        TokenAccount::LEN,
+        &token::ID,
+        &[b"vault", realm.as_ref(), &[bump]],
+    )?;
+
+    token::initialize_account3(CpiContext::new(
+        accounts.token_program.to_account_info(),
+        token::InitializeAccount3 {
+            account: accounts.vault.to_account_info(),
+            mint: accounts.governing_mint.to_account_info(),
+            authority: accounts.vault.to_account_info(),
+        },
+    ))
+}
+
+/// Context for depositing governing tokens in the vault of a realm
+#[derive(Accounts)]
+pub struct DepositGoverningTokens<'info> {
+    #[account(init_if_needed, payer = signer, space = 8 + VoterRecord::INIT_SPACE, seeds = [b"voter", realm.key().as_ref(), signer.key().as_ref()], bump)]
+    pub voter_record: Account<'info, VoterRecord>,
+    /// CHECK: token account owned by itself, created on the first deposit in the realm
+    #[account(mut, seeds = [b"vault", realm.key().as_ref()], bump)]
+    pub vault: UncheckedAccount<'info>,
+    pub realm: Account<'info, Realm>,
+    #[account(address = realm.governing_mint @ ProposalError::InvalidTokenAccount)]
+    pub governing_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = source.mint == realm.governing_mint @ ProposalError::InvalidTokenAccount,
+        constraint = source.owner == signer.key() @ ProposalError::InvalidTokenAccount,
+    )]
+    pub source: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for withdrawing governing tokens from the vault of a realm
+#[derive(Accounts)]
+pub struct WithdrawGoverningTokens<'info> {
+    #[account(mut, seeds = [b"voter", realm.key().as_ref(), signer.key().as_ref()], bump)]
+    pub voter_record: Account<'info, VoterRecord>,
+    #[account(mut, seeds = [b"vault", realm.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    pub realm: Account<'info, Realm>,
+    #[account(mut, constraint = destination.mint == realm.governing_mint @ ProposalError::InvalidTokenAccount)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub signer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Context for delegating voting power
+#[derive(Accounts)]
+pub struct InitializeDelegation<'info> {
+    #[account(init, payer = signer, space = 8 + Delegation::INIT_SPACE, seeds = [b"delegation", realm.key().as_ref(), signer.key().as_ref()], bump)]
+    pub delegation: Account<'info, Delegation>,
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for removing a delegation
+#[derive(Accounts)]
+pub struct CloseDelegation<'info> {
+    #[account(mut, close = signer, seeds = [b"delegation", realm.key().as_ref(), signer.key().as_ref()], bump)]
+    pub delegation: Account<'info, Delegation>,
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// Context for casting a vote
+#[derive(Accounts)]
+pub struct InitializeVote<'info> {
+    #[account(init, space = 8 + Voting::INIT_SPACE, payer=signer, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: Account<'info, Voting>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, seeds = [b"voter", proposal.realm.as_ref(), signer.key().as_ref()], bump)]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for changing a vote
+#[derive(Accounts)]
+pub struct ChangeVote<'info> {
+    #[account(mut, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: Account<'info, Voting>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for revealing a secret vote
+#[derive(Accounts)]
+pub struct RevealVote<'info> {
+    #[account(mut, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: Account<'info, Voting>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for withdrawing a vote
+#[derive(Accounts)]
+pub struct WithdrawVote<'info> {
+    #[account(mut, close = payer, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: Account<'info, Voting>,
+    /// CHECK: account that paid the rent of the receipt, recorded in it, which gets the rent back.
+    #[account(mut, address = vote.payer @ ProposalError::InvalidVoteReceipt)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, seeds = [b"voter", vote.realm.as_ref(), signer.key().as_ref()], bump)]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for starting a proposal
+#[derive(Accounts)]
+pub struct StartProposal<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for finalizing a proposal
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for counting ranked votes
+#[derive(Accounts)]
+pub struct TallyRankedVotes<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for executing a proposal
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: governance authority signing the stored instructions, it only holds lamports.
+    #[account(mut, seeds = [b"governance", proposal.realm.as_ref()], bump)]
+    pub governance: UncheckedAccount<'info>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for cancelling a proposal
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(address = proposal.realm @ ProposalError::NotAuthorized)]
+    pub realm: Account<'info, Realm>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for archiving the results of a proposal
+#[derive(Accounts)]
+pub struct ArchiveProposal<'info> {
+    #[account(init, payer = signer, space = ProposalResult::space(proposal.votes.len()), seeds = [b"result", proposal.key().as_ref()], bump)]
+    pub result: Account<'info, ProposalResult>,
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for deleting a proposal
+#[derive(Accounts)]
+pub struct DeleteProposal<'info> {
+    #[account(mut, close=signer)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(address = proposal.realm @ ProposalError::InvalidRealm)]
+    pub realm: Account<'info, Realm>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for closing the vote receipt of the signer
+#[derive(Accounts)]
+pub struct CloseVoteReceipt<'info> {
+    #[account(mut, close = payer, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: Account<'info, Voting>,
+    /// CHECK: account that paid the rent of the receipt, recorded in it, which gets the rent back.
+    #[account(mut, address = vote.payer @ ProposalError::InvalidVoteReceipt)]
+    pub payer: UncheckedAccount<'info>,
+    /// CHECK: proposal of the receipt, bound by the seeds of the receipt; it may have been deleted.
+    pub proposal: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"voter", vote.realm.as_ref(), signer.key().as_ref()], bump)]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for closing vote receipts on behalf of their voters
+#[derive(Accounts)]
+pub struct CloseVoteReceipts<'info> {
+    /// CHECK: proposal of the receipts, each receipt is checked against it; it may have been deleted.
+    pub proposal: UncheckedAccount<'info>,
+
+    pub signer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for migrating a proposal to the current layout
+#[derive(Accounts)]
+pub struct MigrateProposal<'info> {
+    /// CHECK: proposal in the previous layout, which `Account<Proposal>` cannot read; its owner is checked here,
+    /// its discriminator and size by the instruction, which closes it.
+    #[account(mut, owner = crate::ID)]
+    pub legacy: UncheckedAccount<'info>,
+    #[account(init, payer = signer, space = 8 + Proposal::INIT_SPACE, seeds = [b"proposal", realm.key().as_ref(), realm.proposal_count.to_le_bytes().as_ref()], bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, has_one = authority @ ProposalError::NotRealmAuthority)]
+    pub realm: Account<'info, Realm>,
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Context for closing a vote receipt of the first version of the program
+#[derive(Accounts)]
+pub struct CloseLegacyVoteReceipt<'info> {
+    /// CHECK: receipt in the previous layout, which `Account<Voting>` cannot read; its owner and address are checked here,
+    /// its discriminator and size by the instruction, which closes it.
+    #[account(mut, owner = crate::ID, seeds = [b"vote", proposal.key().as_ref(), signer.key().as_ref()], bump)]
+    pub vote: UncheckedAccount<'info>,
+    /// CHECK: legacy proposal of the receipt, bound by the seeds of the receipt; it may have been migrated.
+    pub proposal: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+// This module contains the account structures and their associated constraints for the voting program.
+
+// Structures representing the accounts used in the voting program.
+
+/// Structure representing a realm, the container of the proposals of a DAO
+#[account]
+#[derive(InitSpace)]
+pub struct Realm {
+    pub authority: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    pub governing_mint: Pubkey,
+    pub quorum: u64,
+    pub approval_threshold: u8,
+    pub proposal_count: u64,
+    /// Time in seconds a proposal must stay closed before it can be deleted
+    pub deletion_delay: u64,
+    /// Minimum time in seconds the vote of a proposal must stay open
+    pub min_voting_time: u64,
+    /// Time in seconds a proposal waits after its vote closes before its instructions can be executed
+    pub hold_up_time: u64,
+}
+
+/// Structure representing a proposal
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    #[max_len(128)]
+    pub title: String,
+    /// URI of the text body of the proposal, stored off-chain
+    #[max_len(200)]
+    pub content_uri: String,
+    /// SHA-256 hash of the text body, to check the content served at the URI
+    pub content_hash: [u8; 32],
+
+    /// Tally of each choice, in the order of the choice indexes; the names are stored in the `ChoiceAccount`s
+    #[max_len(32)]
+    pub votes: Vec<u128>,
+    pub date_start: u64,
+    pub date_end: u64,
+    /// End of the reveal window of secret ballots, `None` for public votes
+    pub reveal_end: Option<u64>,
+    pub voting_type: VotingType,
+    /// Instant-runoff count of ranked ballots, `None` for other voting types
+    pub ranked_tally: Option<RankedTally>,
+    /// Total weight of the approval ballots, each counted once however many choices it approves
+    pub approval_weight: u128,
+    pub creator: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+    pub governing_mint: Pubkey,
+
+    #[max_len(4)]
+    pub instructions: Vec<ProposalInstruction>,
+
+    pub quorum: u64,
+    pub approval_threshold: u8,
+    /// Time in seconds the proposal waits after its vote closes before its instructions can be executed
+    pub hold_up_time: u64,
+    pub outcome: Option<ProposalOutcome>,
+    pub state: ProposalState,
+}
+
+impl Proposal {
+    /// Returns the time after which the proposal can be finalized: the end of the reveal period
+    /// for secret ballots, the end date otherwise.
+    pub fn closing_time(&self) -> u64 {
+        self.reveal_end.unwrap_or(self.date_end)
+    }
+
+    /// Returns the time from which the instructions of the proposal can be executed, once its hold-up time
+    /// has elapsed after the closing time.
+    pub fn executable_time(&self) -> u64 {
+        self.closing_time().saturating_add(self.hold_up_time)
+    }
+
+    /// Returns whether the votes are ready to be finalized, that is the instant-runoff count of ranked ballots is complete.
+    pub fn is_tallied(&self) -> bool {
+        match &self.ranked_tally {
+            Some(tally) => tally.complete,
+            None => true,
+        }
+    }
+
+    /// Returns the index of the choice whose weight is strictly greater than every other choice, if any.
+    pub fn winning_choice(&self) -> Option<usize> {
+        let (index, best) = self
+            .votes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, count)| **count)?;
+
+        let tied = self.votes.iter().filter(|count| *count == best).count();
+
+        (*best > 0 && tied == 1).then_some(index)
+    }
+
+    /// Computes the outcome of the proposal from its quorum, approval threshold and current tallies.
+    /// Fails with `ProposalError::TallyOverflow` if the total weight or the threshold comparison overflows.
+    pub fn compute_outcome(&self) -> Result<ProposalOutcome> {
+        let total = match self.voting_type {
+            VotingType::Approval { .. } => Some(self.approval_weight),
+            _ => self
+                .votes
+                .iter()
+                .try_fold(0u128, |total, count| total.checked_add(*count)),
+        }
+        .ok_or(ProposalError::TallyOverflow)?;
+
+        if total == 0 || total < self.quorum as u128 {
+            return Ok(ProposalOutcome::QuorumNotReached);
+        }
+
+        let Some(index) = self.winning_choice() else {
+            return Ok(ProposalOutcome::Defeated);
+        };
+        let support = self.votes[index]
+            .checked_mul(100)
+            .ok_or(ProposalError::TallyOverflow)?;
+        let required = total
+            .checked_mul(self.approval_threshold as u128)
+            .ok_or(ProposalError::TallyOverflow)?;
+
+        if support > required {
+            Ok(ProposalOutcome::Succeeded)
+        } else {
+            Ok(ProposalOutcome::Defeated)
+        }
+    }
+}
+
+/// Lifecycle state of a proposal
+/// The stored state only changes when an instruction writes it, so it can lag behind the clock:
+/// a `Draft` proposal whose start date has passed accepts votes until the first ballot or `start_proposal`
+/// moves it to `Voting`, and a proposal whose end date has passed stays `Draft` or `Voting` until it is finalized.
+/// The dates of the proposal tell whether it is open for votes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalState {
+    /// Created, voting has not started yet, or no instruction has recorded its start
+    Draft,
+    /// Open for votes until the end date
+    Voting,
+    /// Finalized with the `Succeeded` outcome
+    Succeeded,
+    /// Finalized with the `Defeated` outcome
+    Defeated,
+    /// Withdrawn before the end of the vote
+    Cancelled,
+    /// Succeeded and its instructions have been executed
+    Executed,
+    /// Finalized without reaching the quorum
+    Expired,
+}
+
+impl ProposalState {
+    /// Returns whether the proposal still accepts votes and has not been finalized.
+    pub fn is_open(&self) -> bool {
+        matches!(self, ProposalState::Draft | ProposalState::Voting)
+    }
+}
+
+/// Definitive outcome of a proposal, written when it is finalized
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalOutcome {
+    Succeeded,
+    Defeated,
+    QuorumNotReached,
+}
+
+/// Way the voters express their preferences on a proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum VotingType {
+    /// Each voter gives their weight to a single choice
+    SingleChoice,
+    /// Each voter ranks the choices, the winner is decided by instant-runoff
+    Ranked,
+    /// Each voter approves any number of choices, within optional limits
+    Approval {
+        /// Minimum number of approved choices, 1 by default
+        min_selections: Option<u8>,
+        /// Maximum number of approved choices, every choice by default
+        max_selections: Option<u8>,
+    },
+    /// Each voter spreads votes over the choices, `n` votes on a choice costing `n²` credits
+    Quadratic {
+        /// Credit budget of every voter, their deposited governing tokens by default
+        credits: Option<u64>,
+    },
+}
+
+/// Structure representing the instant-runoff count of a ranked proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct RankedTally {
+    /// Number of ranked ballots cast
+    pub ballots: u64,
+    /// Round being counted, starting at 1
+    pub round: u8,
+    /// Number of ballots counted in the current round
+    pub counted: u64,
+    /// Weight supporting each choice in the current round, or in the last round once complete
+    #[max_len(32)]
+    pub counts: Vec<u128>,
+    /// Bit mask of the eliminated choices
+    pub eliminated: u32,
+    /// Choices eliminated at the end of each closed round, in order;
+    /// the counts of every round are emitted in `RankedRoundTallied`
+    #[max_len(32)]
+    pub eliminations: Vec<u8>,
+    pub complete: bool,
+    pub winner: Option<u8>,
+}
+
+impl RankedTally {
+    /// Starts the count of a proposal with the given number of choices.
+    pub fn new(choices: usize) -> Self {
+        RankedTally {
+            round: 1,
+            counts: vec![0; choices],
+            ..RankedTally::default()
+        }
+    }
+
+    /// Returns the first choice of a ranking that has not been eliminated.
+    pub fn preferred_choice(&self, ranking: &[u8]) -> Option<u8> {
+        ranking
+            .iter()
+            .copied()
+            .find(|choice| self.eliminated & (1 << choice) == 0)
+    }
+
+    /// Closes the current round: completes the count if a choice holds a strict majority of the counted weight
+    /// or nothing was counted, eliminates the choice with the lowest weight and starts the next round otherwise.
+    /// Returns the counts of the closed round.
+    /// Fails with `ProposalError::TallyOverflow` if the counted weight overflows.
+    pub fn close_round(&mut self) -> Result<Vec<u128>> {
+        let total = self
+            .counts
+            .iter()
+            .try_fold(0u128, |total, count| total.checked_add(*count))
+            .ok_or(ProposalError::TallyOverflow)?;
+        let remaining: Vec<usize> = (0..self.counts.len())
+            .filter(|choice| self.eliminated & (1 << choice) == 0)
+            .collect();
+
+        let winner = remaining
+            .iter()
+            .copied()
+            .find(|choice| self.counts[*choice] > total / 2);
+
+        if total == 0 || winner.is_some() {
+            self.winner = winner.map(|choice| choice as u8);
+            self.complete = true;
+            return Ok(self.counts.clone());
+        }
+
+        // On a tie for the lowest weight, the choice listed last is eliminated.
+        let eliminated = remaining
+            .iter()
+            .copied()
+            .min_by_key(|choice| (self.counts[*choice], std::cmp::Reverse(*choice)))
+            .unwrap();
+
+        self.eliminations.push(eliminated as u8);
+        self.eliminated |= 1 << eliminated;
+        self.round += 1;
+        self.counted = 0;
+        let next = vec![0; self.counts.len()];
+        Ok(std::mem::replace(&mut self.counts, next))
+    }
+}
+
+/// Structure representing a choice of a proposal, stored apart so that a proposal can have many choices.
+/// Its address is derived from the proposal and the hash of its name, so names are unique within a proposal.
+#[account]
+#[derive(InitSpace)]
+pub struct ChoiceAccount {
+    pub proposal: Pubkey,
+    /// Position of the choice, used by the votes to designate it
+    pub index: u8,
+    #[max_len(64)]
+    pub name: String,
+}
+
+/// Structure representing the archived results of a proposal, kept once the proposal is deleted
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalResult {
+    pub proposal: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+    /// SHA-256 hash of the title of the proposal
+    pub title_hash: [u8; 32],
+    /// SHA-256 hash of the text body of the proposal
+    pub content_hash: [u8; 32],
+    /// Final tally of each choice
+    #[max_len(32)]
+    pub votes: Vec<u128>,
+    /// Index of the choice that made the proposal pass
+    pub winner: Option<u8>,
+    pub outcome: Option<ProposalOutcome>,
+    pub state: ProposalState,
+    pub date_start: u64,
+    pub date_end: u64,
+    pub archived_at: u64,
+}
+
+impl ProposalResult {
+    /// Returns the size of the account, discriminator included, for a proposal with `choices` choices.
+    pub fn space(choices: usize) -> usize {
+        8 + Self::INIT_SPACE - (MAX_CHOICES - choices) * 16
+    }
+}
+
+/// Structure representing an instruction executed when a proposal passes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    #[max_len(10)]
+    pub accounts: Vec<ProposalAccountMeta>,
+    #[max_len(256)]
+    pub data: Vec<u8>,
+}
+
+/// Structure representing an account used by a proposal instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&ProposalInstruction> for Instruction {
+    fn from(instruction: &ProposalInstruction) -> Self {
+        Instruction {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        }
+    }
+}
+
+/// Structure representing the delegation of a member's voting power in a realm
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// Structure representing the governing tokens deposited by a member in the vault of a realm
+#[account]
+#[derive(InitSpace)]
+pub struct VoterRecord {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    /// Governing tokens held in the vault for the member, their voting weight
+    pub deposited: u64,
+    /// Vote receipts counting the deposit, which cannot be withdrawn until they are all withdrawn or closed
+    pub active_votes: u64,
 }
 
 /// Structure representing a vote cast by a voter
 #[account]
 #[derive(InitSpace)]
 pub struct Voting {
+    /// Index of the chosen choice, or of the first preference of a ranked vote;
+    /// `None` for a secret vote not revealed yet and for approval or quadratic votes
+    pub choice: Option<u8>,
+    pub voter: Pubkey,
+    pub proposal: Pubkey,
+    /// Realm of the proposal, whose voter record counts the receipt until it is withdrawn or closed
+    pub realm: Pubkey,
+    pub weight: u64,
+    pub timestamp: u64,
+    /// Hash of the hidden choice of a secret vote until it is revealed
+    pub commitment: Option<[u8; 32]>,
+    /// Indexes of the choices of a ranked vote, from the preferred one, or of an approval vote
+    #[max_len(32)]
+    pub choices: Vec<u8>,
+    /// Last instant-runoff round the ranked vote was counted in
+    pub tallied_round: u8,
+    /// Number of votes given to each choice by a quadratic vote
+    #[max_len(32)]
+    pub votes: Vec<u64>,
+    /// Credits spent by a quadratic vote
+    pub credits_spent: u64,
+    /// Account that paid the rent of the receipt, the voter or the delegate who voted for them,
+    /// refunded when the receipt is closed
+    pub payer: Pubkey,
+}
+
+// Previous layout of the proposals, kept to migrate the accounts created with it.
+
+/// Structure representing a proposal of the first version of the program, read by `migrate_proposal`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyProposal {
+    #[max_len(64)]
+    pub description: String,
+    #[max_len(64)]
+    pub title: String,
+
+    #[max_len(5)]
+    pub votes: Vec<LegacyChoice>,
+    pub date_start: u64,
+    pub date_end: u64,
+    pub creator: Pubkey,
+}
+
+/// Structure representing a choice of the first version of the program, whose tally counts wallets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyChoice {
+    #[max_len(64)]
+    pub name: String,
+    pub count: u16,
+}
+
+/// Structure representing a vote receipt of the first version of the program, closed by `close_legacy_vote_receipt`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyVoting {
     #[max_len(64)]
     pub choice: String,
     pub voter: Pubkey,
     pub proposal: Pubkey,
 }
 
-/// This module contains the error codes used in the voting program.
+impl LegacyProposal {
+    /// Converts the proposal to the current layout as the next proposal of a realm,
+    /// with the given content in place of its description.
+    /// The proposal takes the governing mint and the voting rules of the realm and becomes a public single choice vote,
+    /// in the `Draft` state until its start date.
+    /// The tallies count wallets: they are kept if the vote has ended at `timestamp`, and reset otherwise
+    /// so that the votes cast afterwards, weighted by deposits, are not added to them.
+    /// The names of the choices are not kept: they are stored in `ChoiceAccount`s.
+    pub fn migrate(
+        self,
+        realm: Pubkey,
+        config: &Realm,
+        content_uri: String,
+        content_hash: [u8; 32],
+        timestamp: u64,
+    ) -> Proposal {
+        let ended = self.date_end <= timestamp;
+
+        Proposal {
+            title: self.title,
+            content_uri,
+            content_hash,
+            votes: self
+                .votes
+                .into_iter()
+                .map(|choice| if ended { choice.count as u128 } else { 0 })
+                .collect(),
+            date_start: self.date_start,
+            date_end: self.date_end,
+            reveal_end: None,
+            voting_type: VotingType::SingleChoice,
+            ranked_tally: None,
+            approval_weight: 0,
+            creator: self.creator,
+            realm,
+            index: config.proposal_count,
+            governing_mint: config.governing_mint,
+            instructions: vec![],
+            quorum: config.quorum,
+            approval_threshold: config.approval_threshold,
+            hold_up_time: config.hold_up_time,
+            outcome: None,
+            state: if self.date_start <= timestamp {
+                ProposalState::Voting
+            } else {
+                ProposalState::Draft
+            },
+        }
+    }
+}
+
+// This module contains the events emitted by the voting program.
+
+/// Event emitted when a realm is created
+#[event]
+pub struct RealmCreated {
+    pub realm: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub governing_mint: Pubkey,
+    pub deletion_delay: u64,
+}
+
+/// Event emitted when the configuration of a realm is updated
+#[event]
+pub struct RealmUpdated {
+    pub realm: Pubkey,
+    pub authority: Pubkey,
+    pub quorum: u64,
+    pub approval_threshold: u8,
+    pub deletion_delay: u64,
+    pub min_voting_time: u64,
+    pub hold_up_time: u64,
+}
+
+/// Event emitted when a proposal is created
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+    pub creator: Pubkey,
+    pub title: String,
+    pub content_uri: String,
+    pub content_hash: [u8; 32],
+    pub date_start: u64,
+    pub date_end: u64,
+    pub reveal_end: Option<u64>,
+    pub voting_type: VotingType,
+}
+
+/// Event emitted when choice accounts are created for a proposal
+#[event]
+pub struct ChoicesAdded {
+    pub proposal: Pubkey,
+    /// Index of the first new choice
+    pub first_index: u8,
+    pub names: Vec<String>,
+}
+
+/// Event emitted when a member delegates their voting power
+#[event]
+pub struct DelegationCreated {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// Event emitted when a member removes their delegation
+#[event]
+pub struct DelegationRemoved {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// Event emitted when governing tokens are deposited in the vault of a realm
+#[event]
+pub struct GoverningTokensDeposited {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub deposited: u64,
+}
+
+/// Event emitted when governing tokens are withdrawn from the vault of a realm
+#[event]
+pub struct GoverningTokensWithdrawn {
+    pub realm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub deposited: u64,
+}
+
+/// Event emitted for each vote receipt created, including the ones created on behalf of delegators
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: u8,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a ranked vote is cast
+#[event]
+pub struct RankedVoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub ranking: Vec<u8>,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an approval vote is cast
+#[event]
+pub struct ApprovalVoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choices: Vec<u8>,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a quadratic vote is cast
+#[event]
+pub struct QuadraticVoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub votes: Vec<u64>,
+    pub credits_spent: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a secret vote is committed
+#[event]
+pub struct VoteCommitted {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a secret vote is revealed and counted
+#[event]
+pub struct VoteRevealed {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: u8,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a vote is moved to another choice
+#[event]
+pub struct VoteChanged {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub previous_choice: Option<u8>,
+    pub choice: u8,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a vote is withdrawn
+#[event]
+pub struct VoteWithdrawn {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: Option<u8>,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a vote receipt is closed and its rent refunded to its voter
+#[event]
+pub struct VoteReceiptClosed {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+}
+
+/// Event emitted when an instant-runoff round of a ranked proposal is closed
+#[event]
+pub struct RankedRoundTallied {
+    pub proposal: Pubkey,
+    pub round: u8,
+    pub counts: Vec<u128>,
+    pub eliminated: Option<u8>,
+    pub winner: Option<u8>,
+}
+
+/// Event emitted when a proposal is moved to the `Voting` state by `start_proposal`
+#[event]
+pub struct ProposalStarted {
+    pub proposal: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+    pub timestamp: u64,
+}
+
+/// Event emitted when the outcome of a proposal is written
+#[event]
+pub struct ProposalFinalized {
+    pub proposal: Pubkey,
+    pub outcome: ProposalOutcome,
+    pub state: ProposalState,
+}
+
+/// Event emitted when the instructions of a proposal are executed
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+}
+
+/// Event emitted when the results of a proposal are archived
+#[event]
+pub struct ProposalArchived {
+    pub proposal: Pubkey,
+    pub result: Pubkey,
+    pub winner: Option<u8>,
+    pub state: ProposalState,
+}
+
+/// Event emitted when a proposal is deleted
+#[event]
+pub struct ProposalDeleted {
+    pub proposal: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+}
+
+/// Event emitted when a proposal is cancelled by its creator or the admin authority of its realm
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+    pub canceller: Pubkey,
+}
+
+/// Event emitted when a proposal is migrated to the current layout
+#[event]
+pub struct ProposalMigrated {
+    pub proposal: Pubkey,
+    /// Address of the proposal in the previous layout, closed by the migration
+    pub legacy: Pubkey,
+    pub realm: Pubkey,
+    pub index: u64,
+}
+
+// This module contains the error codes used in the voting program.
 
 /// Error codes for the voting program
 #[error_code]
@@ -218,7 +3194,7 @@ pub enum ProposalError {
     #[msg("La date de début est égale ou plus ultérieur à la date de fin.")]
     DateNotConform,
 
-    #[msg("Le nombre de choix doit être compris entre 2 et 5.")]
+    #[msg("Le nombre de choix doit être compris entre 2 et 32.")]
     InvalidNumberOfChoices,
 
     #[msg("Ce choix n'existe pas dans cette proposition.")]
@@ -230,7 +3206,7 @@ pub enum ProposalError {
     #[msg("Le sondage est clôturé.")]
     VoteClosed,
 
-    #[msg("Vous n'êtes pas autorisé à supprimer ce sondage.")]
+    #[msg("Vous n'êtes pas autorisé à effectuer cette opération sur ce sondage.")]
     NotAuthorized,
 
     #[msg("Le sondage n'est pas terminé.")]
@@ -238,4 +3214,124 @@ pub enum ProposalError {
 
     #[msg("La fermeture du sondage est trop récente pour pouvoir le supprimer.")]
     TooRecentToDelete,
-}
\ No newline at end of file
+
+    #[msg("Le compte de jetons ne correspond pas au jeton de gouvernance ou au votant.")]
+    InvalidTokenAccount,
+
+    #[msg("Vous n'avez déposé aucun jeton de gouvernance.")]
+    NoVotingWeight,
+
+    #[msg("Les instructions de la proposition dépassent la taille autorisée.")]
+    InvalidInstructions,
+
+    #[msg("La proposition n'a pas été adoptée.")]
+    ProposalNotPassed,
+
+    #[msg("Les instructions de la proposition ont déjà été exécutées.")]
+    AlreadyExecuted,
+
+    #[msg("Le seuil d'approbation doit être compris entre 50 et 99 pourcents.")]
+    InvalidThreshold,
+
+    #[msg("Le résultat du sondage a déjà été établi.")]
+    AlreadyFinalized,
+
+    #[msg("L'état du sondage ne permet pas cette opération.")]
+    InvalidProposalState,
+
+    #[msg("Le nom du royaume doit comporter entre 1 et 32 octets.")]
+    InvalidRealmName,
+
+    #[msg("Vous n'êtes pas l'administrateur de ce royaume.")]
+    NotRealmAuthority,
+
+    #[msg("Vous ne pouvez pas vous déléguer votre propre vote.")]
+    InvalidDelegate,
+
+    #[msg("Cette délégation ne vous est pas destinée ou ses comptes ne correspondent pas.")]
+    InvalidDelegation,
+
+    #[msg("Ce membre a déjà voté pour ce sondage.")]
+    AlreadyVoted,
+
+    #[msg("Ce mode de vote n'est pas celui du sondage.")]
+    InvalidVotingMode,
+
+    #[msg("La période de révélation doit durer au moins une seconde.")]
+    InvalidRevealPeriod,
+
+    #[msg("La période de révélation des votes n'est pas ouverte.")]
+    RevealNotOpen,
+
+    #[msg("Le choix et le sel ne correspondent pas à l'engagement du vote.")]
+    InvalidReveal,
+
+    #[msg("Le classement doit contenir des choix distincts du sondage.")]
+    InvalidRanking,
+
+    #[msg("Ce bulletin n'appartient pas au sondage ou a déjà été compté pour ce tour.")]
+    InvalidBallot,
+
+    #[msg("Le dépouillement du vote préférentiel n'est pas terminé.")]
+    TallyNotComplete,
+
+    #[msg("La sélection doit contenir des choix distincts du sondage, en nombre autorisé.")]
+    InvalidSelection,
+
+    #[msg("Les limites de sélection doivent être comprises entre 1 et le nombre de choix.")]
+    InvalidSelectionLimits,
+
+    #[msg("Le budget de crédits doit être d'au moins un crédit.")]
+    InvalidCreditBudget,
+
+    #[msg("La répartition doit indiquer un nombre de voix pour chaque choix, dont au moins un non nul.")]
+    InvalidAllocation,
+
+    #[msg("Le coût des voix dépasse votre budget de crédits.")]
+    BudgetExceeded,
+
+    #[msg("Les noms des choix doivent être uniques, non vides et comporter au plus 64 octets.")]
+    InvalidChoiceName,
+
+    #[msg("Le décompte des votes dépasse sa capacité.")]
+    TallyOverflow,
+
+    #[msg("Ce sondage utilise déjà le format actuel.")]
+    AlreadyMigrated,
+
+    #[msg("Le titre ou l'URI du contenu du sondage est trop long.")]
+    InvalidContent,
+
+    #[msg("Les comptes des choix ne correspondent pas aux choix du sondage.")]
+    InvalidChoiceAccount,
+
+    #[msg("Les choix ne peuvent plus être modifiés une fois le vote ouvert.")]
+    ChoicesLocked,
+
+    #[msg("Le reçu de vote ne correspond pas à ce sondage ou à ce votant.")]
+    InvalidVoteReceipt,
+
+    #[msg("Le délai de suppression ne peut pas dépasser dix ans.")]
+    InvalidDeletionDelay,
+
+    #[msg("Le royaume ne correspond pas à celui du sondage.")]
+    InvalidRealm,
+
+    #[msg("Le registre de dépôt ne correspond pas au royaume ou au votant.")]
+    InvalidVoterRecord,
+
+    #[msg("Vos jetons restent bloqués tant que vos reçus de vote n'ont pas été retirés ou fermés.")]
+    DepositLocked,
+
+    #[msg("Le montant dépasse vos jetons déposés.")]
+    InsufficientDeposit,
+
+    #[msg("Le quorum ne peut pas être inférieur à celui du royaume.")]
+    InvalidQuorum,
+
+    #[msg("La durée du vote est inférieure au minimum fixé par le royaume.")]
+    VotingTimeTooShort,
+
+    #[msg("Le délai d'attente avant l'exécution de la proposition n'est pas écoulé.")]
+    HoldUpNotElapsed,
+}
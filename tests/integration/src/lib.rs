@@ -0,0 +1,375 @@
+//! In-process test harness of the voting_dao program.
+//!
+//! The program runs natively inside `solana-program-test`, next to the SPL Token program.
+//! Running the tests with `cargo test-sbf` loads the built `voting_dao.so` instead.
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::AccountSharedData,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use voting_dao::{ProposalError, ProposalInstruction, VotingType, DEFAULT_DELETION_DELAY};
+pub use voting_dao_client::{instructions::*, pda::*};
+
+/// Unix timestamp the clock is set to when the harness starts.
+pub const NOW: u64 = 1_700_000_000;
+
+/// One day in seconds.
+pub const DAY: u64 = 86_400;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint ties the accounts slice to the lifetime of the accounts themselves.
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    voting_dao::entry(program_id, accounts, data)
+}
+
+/// A funded wallet holding a governing token account.
+pub struct Member {
+    pub keypair: Keypair,
+    pub token_account: Pubkey,
+}
+
+impl Member {
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+/// Parameters of `create_proposal`, with defaults opening a two-choice vote for one day.
+pub struct ProposalParams {
+    pub title: String,
+    pub content_uri: String,
+    pub content_hash: [u8; 32],
+    pub choices: Vec<String>,
+    pub date_start: u64,
+    pub date_end: u64,
+    pub instructions: Vec<ProposalInstruction>,
+    pub quorum: Option<u64>,
+    pub approval_threshold: Option<u8>,
+    pub reveal_period: Option<u64>,
+    pub voting_type: VotingType,
+}
+
+impl From<ProposalParams> for voting_dao::instruction::CreateProposal {
+    fn from(params: ProposalParams) -> Self {
+        voting_dao::instruction::CreateProposal {
+            title: params.title,
+            content_uri: params.content_uri,
+            content_hash: params.content_hash,
+            choices: params.choices,
+            date_start: params.date_start,
+            date_end: params.date_end,
+            instructions: params.instructions,
+            quorum: params.quorum,
+            approval_threshold: params.approval_threshold,
+            reveal_period: params.reveal_period,
+            voting_type: params.voting_type,
+        }
+    }
+}
+
+impl Default for ProposalParams {
+    fn default() -> Self {
+        ProposalParams {
+            title: "Budget".to_string(),
+            content_uri: "ipfs://budget".to_string(),
+            content_hash: [1; 32],
+            choices: vec!["Pour".to_string(), "Contre".to_string()],
+            date_start: NOW,
+            date_end: NOW + DAY,
+            instructions: vec![],
+            quorum: None,
+            approval_threshold: None,
+            reveal_period: None,
+            voting_type: VotingType::SingleChoice,
+        }
+    }
+}
+
+/// Test environment running the voting_dao program with a governing mint owned by the payer.
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub mint: Keypair,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let program_test = ProgramTest::new(
+            "voting_dao",
+            voting_dao::ID,
+            processor!(process_instruction),
+        );
+        let context = program_test.start_with_context().await;
+
+        let mut harness = Harness {
+            context,
+            mint: Keypair::new(),
+        };
+        harness.create_mint().await;
+        harness.set_time(NOW).await;
+
+        harness
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Sends the instructions in a transaction paid by the payer and signed by `signers`.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        // A new blockhash keeps retried transactions from being deduplicated.
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Moves the `Clock` sysvar to the given Unix timestamp.
+    pub async fn set_time(&mut self, unix_timestamp: u64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp as i64;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Fetches and decodes an account of the program.
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("account {address} does not exist"));
+
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Replaces the data of an account, keeping its owner and lamports.
+    pub async fn set_data(&mut self, address: Pubkey, data: Vec<u8>) {
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("account {address} does not exist"));
+
+        account.data = data;
+        self.context
+            .set_account(&address, &AccountSharedData::from(account));
+    }
+
+    pub async fn exists(&mut self, address: Pubkey) -> bool {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn transfer(&mut self, to: Pubkey, lamports: u64) {
+        let instruction = system_instruction::transfer(&self.payer(), &to, lamports);
+        self.process(&[instruction], &[]).await.unwrap();
+    }
+
+    async fn create_mint(&mut self) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mint = self.mint.insecure_clone();
+
+        let instructions = [
+            system_instruction::create_account(
+                &self.payer(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &self.payer(),
+                None,
+                0,
+            )
+            .unwrap(),
+        ];
+
+        self.process(&instructions, &[&mint]).await.unwrap();
+    }
+
+    /// Creates a wallet funded with 1 SOL and holding `balance` governing tokens.
+    pub async fn member(&mut self, balance: u64) -> Member {
+        let keypair = Keypair::new();
+        let token_account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+
+        let mut instructions = vec![
+            system_instruction::transfer(&self.payer(), &keypair.pubkey(), 1_000_000_000),
+            system_instruction::create_account(
+                &self.payer(),
+                &token_account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &token_account.pubkey(),
+                &self.mint.pubkey(),
+                &keypair.pubkey(),
+            )
+            .unwrap(),
+        ];
+        if balance > 0 {
+            instructions.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::ID,
+                    &self.mint.pubkey(),
+                    &token_account.pubkey(),
+                    &self.payer(),
+                    &[],
+                    balance,
+                )
+                .unwrap(),
+            );
+        }
+
+        self.process(&instructions, &[&token_account])
+            .await
+            .unwrap();
+
+        Member {
+            keypair,
+            token_account: token_account.pubkey(),
+        }
+    }
+
+    /// Creates a member and deposits its `balance` governing tokens in the vault of the realm.
+    pub async fn voter(&mut self, realm: Pubkey, balance: u64) -> Member {
+        let member = self.member(balance).await;
+        self.deposit(realm, &member, balance).await.unwrap();
+
+        member
+    }
+
+    pub async fn deposit(
+        &mut self,
+        realm: Pubkey,
+        member: &Member,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = deposit_governing_tokens(
+            &realm,
+            &self.mint.pubkey(),
+            &member.pubkey(),
+            &member.token_account,
+            amount,
+        );
+        self.process(&[instruction], &[&member.keypair]).await
+    }
+
+    /// Creates a realm administrated by the payer, with no quorum, a simple majority,
+    /// the default deletion delay of 30 days, no minimum voting time and no hold-up time.
+    pub async fn create_realm(&mut self, name: &str) -> Pubkey {
+        let instruction = create_realm(
+            &self.payer(),
+            &self.mint.pubkey(),
+            name,
+            0,
+            50,
+            DEFAULT_DELETION_DELAY,
+            0,
+            0,
+        );
+        self.process(&[instruction], &[]).await.unwrap();
+
+        realm_address(&self.payer(), name)
+    }
+
+    /// Creates a proposal in the realm and returns its address.
+    pub async fn create_proposal(
+        &mut self,
+        realm: Pubkey,
+        creator: &Keypair,
+        params: ProposalParams,
+    ) -> Result<Pubkey, BanksClientError> {
+        let index = self
+            .account::<voting_dao::Realm>(realm)
+            .await
+            .proposal_count;
+        let instruction = create_proposal(&creator.pubkey(), &realm, index, params.into());
+        self.process(&[instruction], &[creator]).await?;
+
+        Ok(proposal_address(&realm, index))
+    }
+
+    /// Returns the realm of a proposal, which the vote instructions derive the voter records from.
+    pub async fn realm_of(&mut self, proposal: Pubkey) -> Pubkey {
+        self.account::<voting_dao::Proposal>(proposal).await.realm
+    }
+
+    pub async fn cast_vote(
+        &mut self,
+        proposal: Pubkey,
+        voter: &Member,
+        choice: u8,
+    ) -> Result<(), BanksClientError> {
+        let realm = self.realm_of(proposal).await;
+        let instruction = cast_vote(&proposal, &voter.pubkey(), &realm, choice, &[]);
+        self.process(&[instruction], &[&voter.keypair]).await
+    }
+
+    pub async fn finalize_proposal(&mut self, proposal: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = finalize_proposal(&proposal, &self.payer());
+        self.process(&[instruction], &[]).await
+    }
+}
+
+/// Asserts that the transaction failed with the given program error.
+pub fn assert_error(result: Result<(), BanksClientError>, error: ProposalError) {
+    let expected = TransactionError::InstructionError(0, InstructionError::Custom(error.into()));
+
+    match result {
+        Err(err) => assert_eq!(err.unwrap(), expected, "expected {error:?}"),
+        Ok(()) => panic!("expected {error:?}, the transaction succeeded"),
+    }
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::BanksClientError;
+use voting_dao::{Proposal, ProposalError, ProposalOutcome, Voting, VotingType};
+use voting_dao_tests::*;
+
+fn approval_params(min_selections: Option<u8>, max_selections: Option<u8>) -> ProposalParams {
+    ProposalParams {
+        choices: vec![0.to_string(), "Forum".to_string(), "Bot".to_string()],
+        voting_type: VotingType::Approval {
+            min_selections,
+            max_selections,
+        },
+        ..ProposalParams::default()
+    }
+}
+
+async fn approve(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voter: &Member,
+    choices: Vec<u8>,
+) -> Result<(), BanksClientError> {
+    let realm = harness.realm_of(proposal).await;
+    let instruction = cast_approval_vote(&proposal, &voter.pubkey(), &realm, choices);
+    harness.process(&[instruction], &[&voter.keypair]).await
+}
+
+#[tokio::test]
+async fn cast_approval_vote_adds_weight_to_each_choice() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, approval_params(None, None))
+        .await
+        .unwrap();
+
+    approve(&mut harness, proposal, &voter, vec![2, 0])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![5, 0, 5]);
+    assert_eq!(account.approval_weight, 5);
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choices, vec![2, 0]);
+    assert_eq!(receipt.weight, 5);
+}
+
+#[tokio::test]
+async fn cast_approval_vote_enforces_selection_limits() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, approval_params(Some(2), Some(2)))
+        .await
+        .unwrap();
+
+    for choices in [vec![0], vec![0, 1, 2], vec![1, 1], vec![0, 3]] {
+        let result = approve(&mut harness, proposal, &voter, choices).await;
+        assert_error(result, ProposalError::InvalidSelection);
+    }
+
+    approve(&mut harness, proposal, &voter, vec![0, 1])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_invalid_selection_limits() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    for (min, max) in [(Some(0), None), (None, Some(4)), (Some(3), Some(2))] {
+        let result = harness
+            .create_proposal(realm, &creator.keypair, approval_params(min, max))
+            .await;
+        assert_error(result.map(|_| ()), ProposalError::InvalidSelectionLimits);
+    }
+}
+
+#[tokio::test]
+async fn vote_instructions_require_approval_type() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let single = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let approval = harness
+        .create_proposal(realm, &voter.keypair, approval_params(None, None))
+        .await
+        .unwrap();
+
+    let result = harness.cast_vote(approval, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    let result = approve(&mut harness, single, &voter, vec![0]).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+}
+
+#[tokio::test]
+async fn finalize_proposal_counts_each_ballot_once() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 6).await;
+    let bob = harness.voter(realm, 4).await;
+    let carol = harness.voter(realm, 3).await;
+    let proposal = harness
+        .create_proposal(realm, &alice.keypair, approval_params(None, None))
+        .await
+        .unwrap();
+
+    approve(&mut harness, proposal, &alice, vec![0, 1])
+        .await
+        .unwrap();
+    approve(&mut harness, proposal, &bob, vec![1])
+        .await
+        .unwrap();
+    approve(&mut harness, proposal, &carol, vec![1, 2])
+        .await
+        .unwrap();
+
+    // Carol withdraws: her weight leaves both approved choices and the total.
+    let instruction = withdraw_vote(&proposal, &realm, &carol.pubkey(), &carol.pubkey());
+    harness
+        .process(&[instruction], &[&carol.keypair])
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY + 1).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    // Forum is approved by the whole weight of 10, Wiki by 6 of it.
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![6, 10, 0]);
+    assert_eq!(account.approval_weight, 10);
+    assert_eq!(account.winning_choice(), Some(1));
+    assert_eq!(account.outcome, Some(ProposalOutcome::Succeeded));
+}
@@ -0,0 +1,86 @@
+use solana_sdk::hash::hash;
+use voting_dao::{ProposalError, ProposalOutcome, ProposalResult, ProposalState};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn archive_proposal_keeps_results_after_deletion() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 3).await;
+    let bob = harness.voter(realm, 2).await;
+    let proposal = harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &alice, 0).await.unwrap();
+    harness.cast_vote(proposal, &bob, 1).await.unwrap();
+
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+    let instruction = archive_proposal(&proposal, &harness.payer());
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    harness.set_time(NOW + 31 * DAY).await;
+    let choices = [
+        choice_address(&proposal, "Pour"),
+        choice_address(&proposal, "Contre"),
+    ];
+    let instruction = delete_proposal(&proposal, &realm, &alice.pubkey(), &choices);
+    harness
+        .process(&[instruction], &[&alice.keypair])
+        .await
+        .unwrap();
+    assert!(!harness.exists(proposal).await);
+
+    let result: ProposalResult = harness.account(result_address(&proposal)).await;
+    assert_eq!(result.proposal, proposal);
+    assert_eq!(result.realm, realm);
+    assert_eq!(result.index, 0);
+    assert_eq!(result.title_hash, hash(b"Budget").to_bytes());
+    assert_eq!(result.content_hash, [1; 32]);
+    assert_eq!(result.votes, vec![3, 2]);
+    assert_eq!(result.winner, Some(0));
+    assert_eq!(result.outcome, Some(ProposalOutcome::Succeeded));
+    assert_eq!(result.state, ProposalState::Succeeded);
+    assert_eq!(result.date_start, NOW);
+    assert_eq!(result.date_end, NOW + DAY);
+    assert_eq!(result.archived_at, NOW + 2 * DAY);
+}
+
+#[tokio::test]
+async fn archive_proposal_rejects_open_proposal_and_second_call() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &creator, 0).await.unwrap();
+
+    let instruction = archive_proposal(&proposal, &harness.payer());
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[])
+        .await;
+    assert_error(result, ProposalError::InvalidProposalState);
+
+    // A cancelled proposal is archived without an outcome or a winner.
+    let cancel = cancel_proposal(&proposal, &realm, &creator.pubkey());
+    harness
+        .process(&[cancel], &[&creator.keypair])
+        .await
+        .unwrap();
+    harness
+        .process(std::slice::from_ref(&instruction), &[])
+        .await
+        .unwrap();
+
+    let result: ProposalResult = harness.account(result_address(&proposal)).await;
+    assert_eq!(result.votes, vec![1, 0]);
+    assert_eq!(result.winner, None);
+    assert_eq!(result.outcome, None);
+    assert_eq!(result.state, ProposalState::Cancelled);
+
+    let result = harness.process(&[instruction], &[]).await;
+    assert!(result.is_err());
+}
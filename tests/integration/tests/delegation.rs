@@ -0,0 +1,293 @@
+use voting_dao::{Delegation, Proposal, ProposalError, Voting};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn delegate_and_undelegate() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.member(0).await;
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    let delegation: Delegation = harness
+        .account(delegation_address(&realm, &owner.pubkey()))
+        .await;
+    assert_eq!(delegation.realm, realm);
+    assert_eq!(delegation.owner, owner.pubkey());
+    assert_eq!(delegation.delegate, representative.pubkey());
+
+    let instruction = undelegate(&realm, &owner.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+    assert!(
+        !harness
+            .exists(delegation_address(&realm, &owner.pubkey()))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn delegate_rejects_self_delegation() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+
+    let instruction = delegate(&realm, &owner.pubkey(), &owner.pubkey());
+    let result = harness.process(&[instruction], &[&owner.keypair]).await;
+    assert_error(result, ProposalError::InvalidDelegate);
+}
+
+#[tokio::test]
+async fn cast_vote_counts_delegated_weight_once() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    let owner_receipt = vote_address(&proposal, &owner.pubkey());
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        owner_receipt,
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 15);
+
+    let receipt: Voting = harness.account(owner_receipt).await;
+    assert_eq!(receipt.voter, owner.pubkey());
+    assert_eq!(receipt.choice, Some(0));
+    assert_eq!(receipt.weight, 10);
+
+    assert!(harness.cast_vote(proposal, &owner, 1).await.is_err());
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[1], 0);
+}
+
+#[tokio::test]
+async fn cast_vote_creates_prefunded_delegator_receipt() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    // Lamports sent to the receipt address must not keep the delegate from voting.
+    let owner_receipt = vote_address(&proposal, &owner.pubkey());
+    harness.transfer(owner_receipt, 1_000_000).await;
+
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        owner_receipt,
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 15);
+    let receipt: Voting = harness.account(owner_receipt).await;
+    assert_eq!(receipt.voter, owner.pubkey());
+    assert_eq!(receipt.weight, 10);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_delegator_who_already_voted() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &owner, 1).await.unwrap();
+
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        vote_address(&proposal, &owner.pubkey()),
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    let result = harness
+        .process(&[instruction], &[&representative.keypair])
+        .await;
+    assert_error(result, ProposalError::AlreadyVoted);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_delegation_to_someone_else() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let intruder = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        vote_address(&proposal, &owner.pubkey()),
+    )];
+    let instruction = cast_vote(&proposal, &intruder.pubkey(), &realm, 0, &delegators);
+    let result = harness.process(&[instruction], &[&intruder.keypair]).await;
+    assert_error(result, ProposalError::InvalidDelegation);
+}
+
+#[tokio::test]
+async fn change_vote_moves_delegator_receipts() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let stranger = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    let delegation = delegation_address(&realm, &owner.pubkey());
+    let owner_receipt = vote_address(&proposal, &owner.pubkey());
+    let delegators = [(
+        delegation,
+        voter_record_address(&realm, &owner.pubkey()),
+        owner_receipt,
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &stranger, 0).await.unwrap();
+
+    // Only the delegate of the delegation can move the receipt of the delegator.
+    let instruction = change_vote(
+        &proposal,
+        &stranger.pubkey(),
+        1,
+        &[(delegation, owner_receipt)],
+    );
+    let result = harness.process(&[instruction], &[&stranger.keypair]).await;
+    assert_error(result, ProposalError::InvalidDelegation);
+
+    let instruction = change_vote(
+        &proposal,
+        &representative.pubkey(),
+        1,
+        &[(delegation, owner_receipt)],
+    );
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![1, 15]);
+    let receipt: Voting = harness.account(owner_receipt).await;
+    assert_eq!(receipt.choice, Some(1));
+}
+
+#[tokio::test]
+async fn delegator_receipt_rent_returns_to_delegate() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+
+    let owner_receipt = vote_address(&proposal, &owner.pubkey());
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        owner_receipt,
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+    let receipt: Voting = harness.account(owner_receipt).await;
+    assert_eq!(receipt.payer, representative.pubkey());
+
+    // The delegator cannot take back the rent their delegate paid.
+    let instruction = withdraw_vote(&proposal, &realm, &owner.pubkey(), &owner.pubkey());
+    let result = harness.process(&[instruction], &[&owner.keypair]).await;
+    assert_error(result, ProposalError::InvalidVoteReceipt);
+
+    let rent = harness.lamports(owner_receipt).await;
+    let balance = harness.lamports(representative.pubkey()).await;
+    let instruction = withdraw_vote(&proposal, &realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+    assert!(!harness.exists(owner_receipt).await);
+    assert_eq!(
+        harness.lamports(representative.pubkey()).await,
+        balance + rent
+    );
+}
@@ -0,0 +1,489 @@
+use anchor_lang::solana_program::{instruction::AccountMeta, system_instruction, system_program};
+use solana_sdk::signature::{Keypair, Signer};
+use voting_dao::{
+    Proposal, ProposalAccountMeta, ProposalError, ProposalInstruction, ProposalOutcome,
+    ProposalState,
+};
+use voting_dao_tests::*;
+
+/// Thirty days in seconds, the deletion delay of the realms created by the harness.
+const THIRTY_DAYS: u64 = 30 * DAY;
+
+#[tokio::test]
+async fn start_proposal_moves_draft_to_voting() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 3 * DAY,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+
+    let instruction = start_proposal(&proposal, &harness.payer());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::VoteNotOpen);
+
+    // Anyone can record the start of the vote, without casting a ballot.
+    harness.set_time(NOW + DAY).await;
+    let instruction = start_proposal(&proposal, &harness.payer());
+    harness.process(&[instruction], &[]).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.state, ProposalState::Voting);
+
+    let instruction = start_proposal(&proposal, &harness.payer());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidProposalState);
+}
+
+#[tokio::test]
+async fn start_proposal_rejects_ended_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + 2 * DAY).await;
+    let instruction = start_proposal(&proposal, &harness.payer());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::VoteClosed);
+}
+
+#[tokio::test]
+async fn finalize_proposal_rejects_open_vote_and_second_call() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::VoteNotEnded);
+
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::AlreadyFinalized);
+}
+
+#[tokio::test]
+async fn finalize_proposal_records_outcome() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 3).await;
+    let bob = harness.voter(realm, 2).await;
+    let carol = harness.voter(realm, 1).await;
+
+    let succeeded = harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(succeeded, &alice, 0).await.unwrap();
+    harness.cast_vote(succeeded, &bob, 1).await.unwrap();
+
+    let defeated = harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(defeated, &alice, 1).await.unwrap();
+    harness.cast_vote(defeated, &bob, 0).await.unwrap();
+    harness.cast_vote(defeated, &carol, 0).await.unwrap();
+
+    let params = ProposalParams {
+        quorum: Some(10),
+        ..ProposalParams::default()
+    };
+    let expired = harness
+        .create_proposal(realm, &alice.keypair, params)
+        .await
+        .unwrap();
+    harness.cast_vote(expired, &alice, 0).await.unwrap();
+
+    harness.set_time(NOW + 2 * DAY).await;
+    for proposal in [succeeded, defeated, expired] {
+        harness.finalize_proposal(proposal).await.unwrap();
+    }
+
+    let account: Proposal = harness.account(succeeded).await;
+    assert_eq!(account.outcome, Some(ProposalOutcome::Succeeded));
+    assert_eq!(account.state, ProposalState::Succeeded);
+
+    let account: Proposal = harness.account(defeated).await;
+    assert_eq!(account.outcome, Some(ProposalOutcome::Defeated));
+    assert_eq!(account.state, ProposalState::Defeated);
+
+    let account: Proposal = harness.account(expired).await;
+    assert_eq!(account.outcome, Some(ProposalOutcome::QuorumNotReached));
+    assert_eq!(account.state, ProposalState::Expired);
+}
+
+#[tokio::test]
+async fn execute_proposal_runs_stored_instructions_once() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let governance = governance_address(&realm);
+    let recipient = Keypair::new().pubkey();
+    harness.transfer(governance, 1_000_000_000).await;
+
+    let transfer = system_instruction::transfer(&governance, &recipient, 500_000_000);
+    let instruction = ProposalInstruction {
+        program_id: transfer.program_id,
+        accounts: transfer
+            .accounts
+            .iter()
+            .map(|meta| ProposalAccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: transfer.data,
+    };
+    let params = ProposalParams {
+        instructions: vec![instruction],
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, params)
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let remaining_accounts = vec![
+        AccountMeta::new(recipient, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let instruction = execute_proposal(
+        &proposal,
+        &realm,
+        &harness.payer(),
+        remaining_accounts.clone(),
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    assert_eq!(harness.lamports(recipient).await, 500_000_000);
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.state, ProposalState::Executed);
+
+    let instruction = execute_proposal(&proposal, &realm, &harness.payer(), remaining_accounts);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::AlreadyExecuted);
+}
+
+#[tokio::test]
+async fn execute_proposal_rejects_defeated_proposal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 1).await.unwrap();
+
+    let instruction = execute_proposal(&proposal, &realm, &harness.payer(), vec![]);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::ProposalNotPassed);
+
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let instruction = execute_proposal(&proposal, &realm, &harness.payer(), vec![]);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::ProposalNotPassed);
+}
+
+#[tokio::test]
+async fn execute_proposal_waits_for_hold_up_time() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let payer = harness.payer();
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, THIRTY_DAYS, 0, DAY);
+    harness.process(&[instruction], &[]).await.unwrap();
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let instruction = execute_proposal(&proposal, &realm, &payer, vec![]);
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[])
+        .await;
+    assert_error(result, ProposalError::HoldUpNotElapsed);
+
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.process(&[instruction], &[]).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.hold_up_time, DAY);
+    assert_eq!(account.state, ProposalState::Executed);
+}
+
+#[tokio::test]
+async fn cancel_succeeded_proposal_during_hold_up_time() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let payer = harness.payer();
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, THIRTY_DAYS, 0, DAY);
+    harness.process(&[instruction], &[]).await.unwrap();
+    let voter = harness.voter(realm, 1).await;
+    let vetoed = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let executable = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(vetoed, &voter, 0).await.unwrap();
+    harness.cast_vote(executable, &voter, 0).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+    harness.finalize_proposal(vetoed).await.unwrap();
+    harness.finalize_proposal(executable).await.unwrap();
+
+    // Only the realm authority can stop a proposal that passed.
+    let instruction = cancel_proposal(&vetoed, &realm, &voter.pubkey());
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+
+    let instruction = cancel_proposal(&vetoed, &realm, &payer);
+    harness.process(&[instruction], &[]).await.unwrap();
+    let account: Proposal = harness.account(vetoed).await;
+    assert_eq!(account.state, ProposalState::Cancelled);
+
+    let instruction = execute_proposal(&vetoed, &realm, &payer, vec![]);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::ProposalNotPassed);
+
+    // Once the hold-up time has elapsed, the proposal can no longer be cancelled.
+    harness.set_time(NOW + 2 * DAY).await;
+    let instruction = cancel_proposal(&executable, &realm, &payer);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidProposalState);
+}
+
+#[tokio::test]
+async fn cancel_proposal_by_creator_stops_the_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.voter(realm, 3).await;
+    let voter = harness.voter(realm, 2).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &creator, 0).await.unwrap();
+
+    let instruction = cancel_proposal(&proposal, &realm, &creator.pubkey());
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.state, ProposalState::Cancelled);
+    assert_eq!(account.votes[0], 3);
+
+    let result = harness.cast_vote(proposal, &voter, 1).await;
+    assert_error(result, ProposalError::InvalidProposalState);
+
+    harness.set_time(NOW + 2 * DAY).await;
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::AlreadyFinalized);
+}
+
+#[tokio::test]
+async fn cancel_proposal_by_realm_authority() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let intruder = harness.member(0).await;
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+
+    let instruction = cancel_proposal(&proposal, &realm, &intruder.pubkey());
+    let result = harness.process(&[instruction], &[&intruder.keypair]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+
+    // The realm passed must be the one of the proposal.
+    let other = harness.create_realm("other").await;
+    let instruction = cancel_proposal(&proposal, &other, &harness.payer());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+
+    let instruction = cancel_proposal(&proposal, &realm, &harness.payer());
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.state, ProposalState::Cancelled);
+
+    let instruction = cancel_proposal(&proposal, &realm, &harness.payer());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidProposalState);
+}
+
+#[tokio::test]
+async fn cancel_proposal_rejects_ended_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY).await;
+    let instruction = cancel_proposal(&proposal, &realm, &creator.pubkey());
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::VoteClosed);
+}
+
+#[tokio::test]
+async fn delete_proposal_requires_creator() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let intruder = harness.member(0).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY + THIRTY_DAYS + 1).await;
+    let instruction = delete_proposal(&proposal, &realm, &intruder.pubkey(), &[]);
+    let result = harness.process(&[instruction], &[&intruder.keypair]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+}
+
+#[tokio::test]
+async fn delete_proposal_waits_thirty_days_after_end() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let choices = [
+        choice_address(&proposal, "Pour"),
+        choice_address(&proposal, "Contre"),
+    ];
+    let instruction = delete_proposal(&proposal, &realm, &creator.pubkey(), &choices);
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&creator.keypair])
+        .await;
+    assert_error(result, ProposalError::VoteNotEnded);
+
+    harness.set_time(NOW + DAY + THIRTY_DAYS - 1).await;
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&creator.keypair])
+        .await;
+    assert_error(result, ProposalError::TooRecentToDelete);
+
+    harness.set_time(NOW + DAY + THIRTY_DAYS).await;
+    let mut rent = harness.lamports(proposal).await;
+    for choice in choices {
+        rent += harness.lamports(choice).await;
+    }
+    let balance = harness.lamports(creator.pubkey()).await;
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+
+    assert!(!harness.exists(proposal).await);
+    for choice in choices {
+        assert!(!harness.exists(choice).await);
+    }
+    assert_eq!(harness.lamports(creator.pubkey()).await, balance + rent);
+}
+
+#[tokio::test]
+async fn delete_proposal_follows_realm_deletion_delay() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let first = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let second = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    // A test realm deletes its proposals as soon as their vote has ended.
+    let payer = harness.payer();
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, 0, 0, 0);
+    harness.process(&[instruction], &[]).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+    let instruction = delete_proposal(&first, &realm, &creator.pubkey(), &[]);
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+    assert!(!harness.exists(first).await);
+
+    // A realm keeping its proposals 180 days still holds them after 30 days.
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, 180 * DAY, 0, 0);
+    harness.process(&[instruction], &[]).await.unwrap();
+    harness.set_time(NOW + DAY + THIRTY_DAYS).await;
+    let instruction = delete_proposal(&second, &realm, &creator.pubkey(), &[]);
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&creator.keypair])
+        .await;
+    assert_error(result, ProposalError::TooRecentToDelete);
+
+    harness.set_time(NOW + DAY + 180 * DAY).await;
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+    assert!(!harness.exists(second).await);
+}
+
+#[tokio::test]
+async fn delete_proposal_rejects_other_realm() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let other = harness.create_realm("other").await;
+    let creator = harness.member(0).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY + THIRTY_DAYS).await;
+    let instruction = delete_proposal(&proposal, &other, &creator.pubkey(), &[]);
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::InvalidRealm);
+}
@@ -0,0 +1,305 @@
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, AccountSerialize, AnchorSerialize,
+    Discriminator, Space,
+};
+use solana_sdk::{account::AccountSharedData, signature::Signer};
+use voting_dao::{
+    ChoiceAccount, LegacyChoice, LegacyProposal, LegacyVoting, Proposal, ProposalError,
+    ProposalOutcome, ProposalState, Realm, Voting,
+};
+use voting_dao_tests::*;
+
+/// Names of the choices of the legacy proposals.
+fn legacy_names() -> Vec<String> {
+    vec!["Oui".to_string(), "Non".to_string()]
+}
+
+/// Writes a proposal of the first version of the program, at the address derived from its title
+/// and at the size it was created with, where one wallet voted for each choice.
+async fn write_legacy_proposal(harness: &mut Harness, creator: &Member) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(&[b"proposal", b"Budget"], &voting_dao::ID);
+    let legacy = LegacyProposal {
+        description: "Budget annuel".to_string(),
+        title: "Budget".to_string(),
+        votes: legacy_names()
+            .into_iter()
+            .map(|name| LegacyChoice { name, count: 1 })
+            .collect(),
+        date_start: NOW,
+        date_end: NOW + DAY,
+        creator: creator.pubkey(),
+    };
+
+    let mut data = Proposal::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(8 + LegacyProposal::INIT_SPACE, 0);
+    write_program_account(harness, address, data).await;
+
+    address
+}
+
+/// Writes the receipt of the first version of the program of `voter`, who voted for the first choice of `proposal`.
+async fn write_legacy_receipt(harness: &mut Harness, proposal: Pubkey, voter: &Member) -> Pubkey {
+    let address = vote_address(&proposal, &voter.pubkey());
+    let legacy = LegacyVoting {
+        choice: "Oui".to_string(),
+        voter: voter.pubkey(),
+        proposal,
+    };
+
+    let mut data = Voting::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(8 + LegacyVoting::INIT_SPACE, 0);
+    write_program_account(harness, address, data).await;
+
+    address
+}
+
+/// Writes a rent-exempt account owned by the program, as the first version of the program created it.
+async fn write_program_account(harness: &mut Harness, address: Pubkey, data: Vec<u8>) {
+    let rent = harness.context.banks_client.get_rent().await.unwrap();
+    let mut account = AccountSharedData::new(
+        rent.minimum_balance(data.len()),
+        data.len(),
+        &voting_dao::ID,
+    );
+    account.set_data_from_slice(&data);
+    harness.context.set_account(&address, &account);
+}
+
+/// Migrates `legacy` as the proposal number `index` of a realm administrated by the payer of the harness.
+fn migrate(
+    harness: &Harness,
+    legacy: Pubkey,
+    realm: Pubkey,
+    index: u64,
+    signer: &Member,
+    names: &[String],
+) -> Instruction {
+    migrate_proposal(
+        &legacy,
+        &realm,
+        index,
+        &signer.pubkey(),
+        &harness.payer(),
+        names,
+        "ipfs://budget".to_string(),
+        [1; 32],
+    )
+}
+
+#[tokio::test]
+async fn migrate_proposal_moves_first_version_into_realm() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 5).await;
+    let bob = harness.voter(realm, 3).await;
+    harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let legacy = write_legacy_proposal(&mut harness, &alice).await;
+
+    let instruction = cast_vote(&legacy, &bob.pubkey(), &realm, 1, &[]);
+    let result = harness.process(&[instruction], &[&bob.keypair]).await;
+    assert!(result.is_err());
+
+    let instruction = migrate(&harness, legacy, realm, 1, &bob, &legacy_names());
+    let result = harness.process(&[instruction], &[&bob.keypair]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+
+    let rent = harness.lamports(legacy).await;
+    let balance = harness.lamports(alice.pubkey()).await;
+    let instruction = migrate(&harness, legacy, realm, 1, &alice, &legacy_names());
+    harness
+        .process(&[instruction], &[&alice.keypair])
+        .await
+        .unwrap();
+
+    // The proposal moves to the address of its index, the legacy account is closed.
+    let proposal = proposal_address(&realm, 1);
+    assert!(!harness.exists(legacy).await);
+    // The rent of the legacy account goes back to the signer, who pays for the new accounts.
+    let mut paid = harness.lamports(proposal).await;
+    for name in legacy_names() {
+        paid += harness.lamports(choice_address(&proposal, &name)).await;
+    }
+    assert_eq!(
+        harness.lamports(alice.pubkey()).await,
+        balance + rent - paid
+    );
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.title, "Budget");
+    assert_eq!(account.content_uri, "ipfs://budget");
+    // The vote is still open: the wallet counts are reset, every member votes again with their deposit.
+    assert_eq!(account.votes, vec![0, 0]);
+    assert_eq!(account.realm, realm);
+    assert_eq!(account.index, 1);
+    assert_eq!(account.governing_mint, harness.mint.pubkey());
+    assert_eq!(account.state, ProposalState::Voting);
+    let config: Realm = harness.account(realm).await;
+    assert_eq!(config.proposal_count, 2);
+    let choice: ChoiceAccount = harness.account(choice_address(&proposal, "Non")).await;
+    assert_eq!(choice.proposal, proposal);
+    assert_eq!(choice.index, 1);
+    assert_eq!(choice.name, "Non");
+
+    harness.cast_vote(proposal, &bob, 1).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[1], 3);
+}
+
+#[tokio::test]
+async fn migrate_proposal_keeps_tallies_of_ended_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let legacy = write_legacy_proposal(&mut harness, &creator).await;
+
+    harness.set_time(NOW + DAY).await;
+    let instruction = migrate(&harness, legacy, realm, 0, &creator, &legacy_names());
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal_address(&realm, 0)).await;
+    assert_eq!(account.votes, vec![1, 1]);
+}
+
+#[tokio::test]
+async fn close_legacy_vote_receipt_refunds_voter() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.member(0).await;
+    let bob = harness.member(0).await;
+    let legacy = write_legacy_proposal(&mut harness, &alice).await;
+    let receipt = write_legacy_receipt(&mut harness, legacy, &bob).await;
+
+    // Only the voter of the receipt can close it.
+    let mut instruction = close_legacy_vote_receipt(&legacy, &alice.pubkey());
+    instruction.accounts[0].pubkey = receipt;
+    let result = harness.process(&[instruction], &[&alice.keypair]).await;
+    assert!(result.is_err());
+
+    let instruction = migrate(&harness, legacy, realm, 0, &alice, &legacy_names());
+    harness
+        .process(&[instruction], &[&alice.keypair])
+        .await
+        .unwrap();
+
+    let rent = harness.lamports(receipt).await;
+    let balance = harness.lamports(bob.pubkey()).await;
+    let instruction = close_legacy_vote_receipt(&legacy, &bob.pubkey());
+    harness
+        .process(&[instruction], &[&bob.keypair])
+        .await
+        .unwrap();
+    assert!(!harness.exists(receipt).await);
+    assert_eq!(harness.lamports(bob.pubkey()).await, balance + rent);
+}
+
+#[tokio::test]
+async fn close_legacy_vote_receipt_rejects_current_layout() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = close_legacy_vote_receipt(&proposal, &voter.pubkey());
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::InvalidVoteReceipt);
+}
+
+#[tokio::test]
+async fn migrate_proposal_requires_realm_authority() {
+    let mut harness = Harness::start().await;
+    let admin = harness.member(0).await;
+    let creator = harness.member(0).await;
+    let instruction = create_realm(
+        &admin.pubkey(),
+        &harness.mint.pubkey(),
+        "dao",
+        0,
+        50,
+        0,
+        0,
+        0,
+    );
+    harness
+        .process(&[instruction], &[&admin.keypair])
+        .await
+        .unwrap();
+    let realm = realm_address(&admin.pubkey(), "dao");
+    let legacy = write_legacy_proposal(&mut harness, &creator).await;
+
+    // The creator cannot push the proposal into a realm whose admin has not accepted it.
+    let instruction = migrate(&harness, legacy, realm, 0, &creator, &legacy_names());
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::NotRealmAuthority);
+
+    let instruction = migrate_proposal(
+        &legacy,
+        &realm,
+        0,
+        &creator.pubkey(),
+        &admin.pubkey(),
+        &legacy_names(),
+        "ipfs://budget".to_string(),
+        [1; 32],
+    );
+    harness
+        .process(&[instruction], &[&creator.keypair, &admin.keypair])
+        .await
+        .unwrap();
+    let account: Proposal = harness.account(proposal_address(&realm, 0)).await;
+    assert_eq!(account.realm, realm);
+}
+
+#[tokio::test]
+async fn migrate_proposal_rejects_current_layout() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = migrate(&harness, proposal, realm, 1, &creator, &legacy_names());
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::AlreadyMigrated);
+}
+
+#[tokio::test]
+async fn tallies_reject_overflow() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let mut account: Proposal = harness.account(proposal).await;
+    account.votes[0] = u128::MAX - 4;
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data.resize(8 + Proposal::INIT_SPACE, 0);
+    harness.set_data(proposal, data).await;
+
+    let result = harness.cast_vote(proposal, &voter, 0).await;
+    assert_error(result, ProposalError::TallyOverflow);
+
+    harness.cast_vote(proposal, &voter, 1).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::TallyOverflow);
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.outcome, None::<ProposalOutcome>);
+}
@@ -0,0 +1,403 @@
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::signature::Signer;
+use voting_dao::{
+    ChoiceAccount, Proposal, ProposalAccountMeta, ProposalError, ProposalInstruction,
+    ProposalState, Realm, DEFAULT_DELETION_DELAY, MAX_CHOICES,
+};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn create_proposal_uses_realm_counter_and_rules() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let first = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let second = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    assert_eq!(first, proposal_address(&realm, 0));
+    assert_eq!(second, proposal_address(&realm, 1));
+    assert_eq!(harness.account::<Realm>(realm).await.proposal_count, 2);
+
+    let proposal: Proposal = harness.account(second).await;
+    assert_eq!(proposal.title, "Budget");
+    assert_eq!(proposal.content_uri, "ipfs://budget");
+    assert_eq!(proposal.content_hash, [1; 32]);
+    assert_eq!(proposal.realm, realm);
+    assert_eq!(proposal.index, 1);
+    assert_eq!(proposal.governing_mint, harness.mint.pubkey());
+    assert_eq!(proposal.quorum, 0);
+    assert_eq!(proposal.approval_threshold, 50);
+    assert_eq!(proposal.state, ProposalState::Voting);
+    assert_eq!(proposal.votes.len(), 2);
+    assert!(proposal.votes.iter().all(|count| *count == 0));
+
+    let choice: ChoiceAccount = harness.account(choice_address(&second, "Contre")).await;
+    assert_eq!(choice.proposal, second);
+    assert_eq!(choice.index, 1);
+    assert_eq!(choice.name, "Contre");
+}
+
+#[tokio::test]
+async fn create_proposal_starts_in_draft_before_date_start() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        quorum: Some(100),
+        approval_threshold: Some(66),
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+
+    let proposal: Proposal = harness.account(proposal).await;
+    assert_eq!(proposal.state, ProposalState::Draft);
+    assert_eq!(proposal.quorum, 100);
+    assert_eq!(proposal.approval_threshold, 66);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_invalid_number_of_choices() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    for count in [1, MAX_CHOICES + 1] {
+        let params = ProposalParams {
+            choices: (0..count).map(|i| format!("Choix {i}")).collect(),
+            ..ProposalParams::default()
+        };
+        let result = harness
+            .create_proposal(realm, &creator.keypair, params)
+            .await;
+        assert_error(result.map(|_| ()), ProposalError::InvalidNumberOfChoices);
+    }
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_invalid_choice_names() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    for choices in [
+        vec!["Pour".to_string(), "Pour".to_string()],
+        vec!["Pour".to_string(), String::new()],
+        vec!["Pour".to_string(), "c".repeat(65)],
+    ] {
+        let params = ProposalParams {
+            choices,
+            ..ProposalParams::default()
+        };
+        let result = harness
+            .create_proposal(realm, &creator.keypair, params)
+            .await;
+        assert_error(result.map(|_| ()), ProposalError::InvalidChoiceName);
+    }
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_start_after_end() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let params = ProposalParams {
+        date_start: NOW + 2 * DAY,
+        date_end: NOW + DAY,
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::DateNotConform);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_oversized_instructions() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let instruction = ProposalInstruction {
+        program_id: voting_dao::ID,
+        accounts: vec![
+            ProposalAccountMeta {
+                pubkey: realm,
+                is_signer: false,
+                is_writable: false,
+            };
+            11
+        ],
+        data: vec![],
+    };
+    let params = ProposalParams {
+        instructions: vec![instruction],
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidInstructions);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_invalid_threshold() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let params = ProposalParams {
+        approval_threshold: Some(30),
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidThreshold);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_rules_below_realm() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let payer = harness.payer();
+    let instruction = update_realm(
+        &payer,
+        &realm,
+        &payer,
+        100,
+        66,
+        DEFAULT_DELETION_DELAY,
+        0,
+        0,
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    // A lower quorum or threshold would let a small minority pass a proposal.
+    let params = ProposalParams {
+        quorum: Some(1),
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidQuorum);
+
+    let params = ProposalParams {
+        approval_threshold: Some(50),
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidThreshold);
+
+    let params = ProposalParams {
+        quorum: Some(200),
+        approval_threshold: Some(75),
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.quorum, 200);
+    assert_eq!(account.approval_threshold, 75);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_voting_time_below_realm_minimum() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let payer = harness.payer();
+    let instruction = update_realm(
+        &payer,
+        &realm,
+        &payer,
+        0,
+        50,
+        DEFAULT_DELETION_DELAY,
+        2 * DAY,
+        0,
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    // A start date in the past does not count: the vote is only open from the creation.
+    for date_start in [NOW, NOW - 3 * DAY] {
+        let params = ProposalParams {
+            date_start,
+            date_end: NOW + DAY,
+            ..ProposalParams::default()
+        };
+        let result = harness
+            .create_proposal(realm, &creator.keypair, params)
+            .await;
+        assert_error(result.map(|_| ()), ProposalError::VotingTimeTooShort);
+    }
+
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 3 * DAY,
+        ..ProposalParams::default()
+    };
+    harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_long_content() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    for params in [
+        ProposalParams {
+            title: "t".repeat(129),
+            ..ProposalParams::default()
+        },
+        ProposalParams {
+            content_uri: "u".repeat(201),
+            ..ProposalParams::default()
+        },
+    ] {
+        let result = harness
+            .create_proposal(realm, &creator.keypair, params)
+            .await;
+        assert_error(result.map(|_| ()), ProposalError::InvalidContent);
+    }
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_mismatched_choice_accounts() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let args = ProposalParams::default().into();
+    let mut instruction = create_proposal(&creator.pubkey(), &realm, 0, args);
+    let last = instruction.accounts.len() - 1;
+    instruction.accounts.swap(last - 1, last);
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::InvalidChoiceAccount);
+}
+
+#[tokio::test]
+async fn create_proposal_creates_prefunded_choice_accounts() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    // The choice addresses of the next proposal are predictable: lamports sent to them must not block it.
+    let proposal = proposal_address(&realm, 0);
+    harness
+        .transfer(choice_address(&proposal, "Pour"), 1_000_000)
+        .await;
+    harness
+        .transfer(choice_address(&proposal, "Contre"), 1_000_000)
+        .await;
+
+    harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let choice: ChoiceAccount = harness.account(choice_address(&proposal, "Pour")).await;
+    assert_eq!(choice.proposal, proposal);
+    assert_eq!(choice.index, 0);
+    assert_eq!(choice.name, "Pour");
+    assert_eq!(harness.account::<Realm>(realm).await.proposal_count, 1);
+}
+
+/// Creates a proposal opening in one day, so that choices can still be added.
+async fn draft_proposal(harness: &mut Harness, realm: Pubkey, creator: &Member) -> Pubkey {
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        ..ProposalParams::default()
+    };
+    harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn add_choices_extends_proposal_up_to_the_limit() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = draft_proposal(&mut harness, realm, &creator).await;
+
+    let names: Vec<String> = (2..MAX_CHOICES).map(|i| format!("Choix {i}")).collect();
+    for page in names.chunks(10) {
+        let instruction = add_choices(&proposal, &creator.pubkey(), page.to_vec());
+        harness
+            .process(&[instruction], &[&creator.keypair])
+            .await
+            .unwrap();
+    }
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes.len(), MAX_CHOICES);
+    let choice: ChoiceAccount = harness.account(choice_address(&proposal, "Choix 31")).await;
+    assert_eq!(choice.index, 31);
+
+    let instruction = add_choices(&proposal, &creator.pubkey(), vec!["Choix 32".to_string()]);
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::InvalidNumberOfChoices);
+
+    harness.set_time(NOW + DAY).await;
+    harness.cast_vote(proposal, &voter, 31).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[31], 5);
+}
+
+#[tokio::test]
+async fn add_choices_rejects_names_already_used() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let proposal = draft_proposal(&mut harness, realm, &creator).await;
+
+    let instruction = add_choices(&proposal, &creator.pubkey(), vec!["Pour".to_string()]);
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::InvalidChoiceName);
+}
+
+#[tokio::test]
+async fn add_choices_requires_creator_before_start() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let intruder = harness.member(0).await;
+    let proposal = draft_proposal(&mut harness, realm, &creator).await;
+
+    let choices = vec!["Abstention".to_string()];
+    let instruction = add_choices(&proposal, &intruder.pubkey(), choices.clone());
+    let result = harness.process(&[instruction], &[&intruder.keypair]).await;
+    assert_error(result, ProposalError::NotAuthorized);
+
+    harness.set_time(NOW + DAY).await;
+    let instruction = add_choices(&proposal, &creator.pubkey(), choices);
+    let result = harness.process(&[instruction], &[&creator.keypair]).await;
+    assert_error(result, ProposalError::ChoicesLocked);
+}
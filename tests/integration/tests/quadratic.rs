@@ -0,0 +1,163 @@
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::BanksClientError;
+use voting_dao::{quadratic_cost, Proposal, ProposalError, Voting, VotingType};
+use voting_dao_tests::*;
+
+fn quadratic_params(credits: Option<u64>) -> ProposalParams {
+    ProposalParams {
+        choices: vec![0.to_string(), "Forum".to_string(), "Bot".to_string()],
+        voting_type: VotingType::Quadratic { credits },
+        ..ProposalParams::default()
+    }
+}
+
+async fn spread(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voter: &Member,
+    votes: Vec<u64>,
+) -> Result<(), BanksClientError> {
+    let realm = harness.realm_of(proposal).await;
+    let instruction = cast_quadratic_vote(&proposal, &voter.pubkey(), &realm, votes);
+    harness.process(&[instruction], &[&voter.keypair]).await
+}
+
+#[test]
+fn quadratic_cost_sums_squares() {
+    assert_eq!(quadratic_cost(&[3, 2, 1]), Some(14));
+    assert_eq!(quadratic_cost(&[0, 0]), Some(0));
+    assert_eq!(quadratic_cost(&[u64::MAX]), Some((u64::MAX as u128).pow(2)));
+    assert_eq!(quadratic_cost(&[u64::MAX, u64::MAX]), None);
+}
+
+#[tokio::test]
+async fn cast_quadratic_vote_spends_token_balance() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 14).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, quadratic_params(None))
+        .await
+        .unwrap();
+
+    spread(&mut harness, proposal, &voter, vec![3, 2, 1])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![3, 2, 1]);
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.votes, vec![3, 2, 1]);
+    assert_eq!(receipt.credits_spent, 14);
+    assert_eq!(receipt.weight, 14);
+}
+
+#[tokio::test]
+async fn cast_quadratic_vote_rejects_ballots_over_budget() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 12).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, quadratic_params(None))
+        .await
+        .unwrap();
+
+    let result = spread(&mut harness, proposal, &voter, vec![3, 2, 0]).await;
+    assert_error(result, ProposalError::BudgetExceeded);
+
+    let result = spread(&mut harness, proposal, &voter, vec![u64::MAX, 0, 0]).await;
+    assert_error(result, ProposalError::BudgetExceeded);
+}
+
+#[tokio::test]
+async fn cast_quadratic_vote_uses_fixed_allotment() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let whale = harness.voter(realm, 1_000).await;
+    let member = harness.voter(realm, 1).await;
+    let outsider = harness.voter(realm, 0).await;
+    let proposal = harness
+        .create_proposal(realm, &whale.keypair, quadratic_params(Some(9)))
+        .await
+        .unwrap();
+
+    let result = spread(&mut harness, proposal, &whale, vec![4, 0, 0]).await;
+    assert_error(result, ProposalError::BudgetExceeded);
+    spread(&mut harness, proposal, &whale, vec![3, 0, 0])
+        .await
+        .unwrap();
+    spread(&mut harness, proposal, &member, vec![0, 2, 2])
+        .await
+        .unwrap();
+
+    let result = spread(&mut harness, proposal, &outsider, vec![1, 0, 0]).await;
+    assert_error(result, ProposalError::NoVotingWeight);
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![3, 2, 2]);
+}
+
+#[tokio::test]
+async fn quadratic_ballots_require_valid_allocation_and_type() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 10).await;
+    let single = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let quadratic = harness
+        .create_proposal(realm, &voter.keypair, quadratic_params(None))
+        .await
+        .unwrap();
+
+    for votes in [vec![1, 1], vec![0, 0, 0], vec![1, 0, 0, 0]] {
+        let result = spread(&mut harness, quadratic, &voter, votes).await;
+        assert_error(result, ProposalError::InvalidAllocation);
+    }
+
+    let result = spread(&mut harness, single, &voter, vec![1, 0]).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+    let result = harness.cast_vote(quadratic, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    let result = harness
+        .create_proposal(realm, &voter.keypair, quadratic_params(Some(0)))
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidCreditBudget);
+}
+
+#[tokio::test]
+async fn withdraw_quadratic_vote_removes_its_votes() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 9).await;
+    let bob = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &alice.keypair, quadratic_params(None))
+        .await
+        .unwrap();
+
+    spread(&mut harness, proposal, &alice, vec![2, 2, 1])
+        .await
+        .unwrap();
+    spread(&mut harness, proposal, &bob, vec![0, 2, 1])
+        .await
+        .unwrap();
+
+    let instruction = withdraw_vote(&proposal, &realm, &alice.pubkey(), &alice.pubkey());
+    harness
+        .process(&[instruction], &[&alice.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes, vec![0, 2, 1]);
+
+    // Alice can spend her whole budget again.
+    spread(&mut harness, proposal, &alice, vec![0, 0, 3])
+        .await
+        .unwrap();
+}
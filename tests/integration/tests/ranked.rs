@@ -0,0 +1,218 @@
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::BanksClientError;
+use voting_dao::{Proposal, ProposalError, ProposalOutcome, Voting, VotingType};
+use voting_dao_tests::*;
+
+/// Creates a ranked proposal with three choices, open for one day.
+async fn ranked_proposal(harness: &mut Harness, realm: Pubkey, creator: &Member) -> Pubkey {
+    let params = ProposalParams {
+        choices: vec![0.to_string(), "Bravo".to_string(), "Charlie".to_string()],
+        voting_type: VotingType::Ranked,
+        ..ProposalParams::default()
+    };
+    harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap()
+}
+
+async fn rank(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voter: &Member,
+    ranking: Vec<u8>,
+) -> Result<(), BanksClientError> {
+    let realm = harness.realm_of(proposal).await;
+    let instruction = cast_ranked_vote(&proposal, &voter.pubkey(), &realm, ranking);
+    harness.process(&[instruction], &[&voter.keypair]).await
+}
+
+async fn tally(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voters: &[&Member],
+) -> Result<(), BanksClientError> {
+    let receipts: Vec<Pubkey> = voters
+        .iter()
+        .map(|voter| vote_address(&proposal, &voter.pubkey()))
+        .collect();
+    let instruction = tally_ranked_votes(&proposal, &harness.payer(), &receipts);
+    harness.process(&[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn cast_ranked_vote_records_ranking_and_first_preference() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = ranked_proposal(&mut harness, realm, &voter).await;
+
+    rank(&mut harness, proposal, &voter, vec![2, 0])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[2], 5);
+    assert_eq!(account.ranked_tally.unwrap().ballots, 1);
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choices, vec![2, 0]);
+    assert_eq!(receipt.choice, Some(2));
+    assert_eq!(receipt.weight, 5);
+}
+
+#[tokio::test]
+async fn cast_ranked_vote_rejects_invalid_rankings() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = ranked_proposal(&mut harness, realm, &voter).await;
+
+    for ranking in [vec![], vec![0, 0], vec![3], vec![0, 1, 2, 1]] {
+        let result = rank(&mut harness, proposal, &voter, ranking).await;
+        assert_error(result, ProposalError::InvalidRanking);
+    }
+}
+
+#[tokio::test]
+async fn vote_instructions_require_matching_type() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let single = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let ranked = ranked_proposal(&mut harness, realm, &voter).await;
+
+    let result = harness.cast_vote(ranked, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    let result = rank(&mut harness, single, &voter, vec![0, 1]).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    let params = ProposalParams {
+        voting_type: VotingType::Ranked,
+        reveal_period: Some(DAY),
+        ..ProposalParams::default()
+    };
+    let result = harness.create_proposal(realm, &voter.keypair, params).await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidVotingMode);
+}
+
+#[tokio::test]
+async fn tally_ranked_votes_runs_instant_runoff_in_pages() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 4).await;
+    let bob = harness.voter(realm, 3).await;
+    let carol = harness.voter(realm, 2).await;
+    let proposal = ranked_proposal(&mut harness, realm, &alice).await;
+
+    rank(&mut harness, proposal, &alice, vec![0]).await.unwrap();
+    rank(&mut harness, proposal, &bob, vec![1, 0])
+        .await
+        .unwrap();
+    rank(&mut harness, proposal, &carol, vec![2, 1])
+        .await
+        .unwrap();
+
+    let result = tally(&mut harness, proposal, &[&alice]).await;
+    assert_error(result, ProposalError::VoteNotEnded);
+
+    harness.set_time(NOW + DAY + 1).await;
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::TallyNotComplete);
+
+    // First round: Alpha 4, Bravo 3, Charlie 2, no majority of 9, Charlie is eliminated.
+    tally(&mut harness, proposal, &[&alice, &bob])
+        .await
+        .unwrap();
+    let result = tally(&mut harness, proposal, &[&bob]).await;
+    assert_error(result, ProposalError::InvalidBallot);
+    tally(&mut harness, proposal, &[&carol]).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    let ranked_tally = account.ranked_tally.unwrap();
+    assert_eq!(ranked_tally.round, 2);
+    assert_eq!(ranked_tally.counts, vec![0, 0, 0]);
+    assert_eq!(ranked_tally.eliminations, vec![2]);
+    assert!(!ranked_tally.complete);
+
+    // Second round: Charlie's ballot moves to Bravo, which wins 5 to 4.
+    tally(&mut harness, proposal, &[&alice, &bob, &carol])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    let ranked_tally = account.ranked_tally.clone().unwrap();
+    assert!(ranked_tally.complete);
+    assert_eq!(ranked_tally.winner, Some(1));
+    assert_eq!(ranked_tally.eliminations, vec![2]);
+    assert_eq!(ranked_tally.counts, vec![4, 5, 0]);
+    assert_eq!(account.votes, vec![4, 5, 0]);
+
+    let result = tally(&mut harness, proposal, &[&alice]).await;
+    assert_error(result, ProposalError::AlreadyFinalized);
+
+    harness.finalize_proposal(proposal).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.outcome, Some(ProposalOutcome::Succeeded));
+    assert_eq!(account.winning_choice(), Some(1));
+}
+
+#[tokio::test]
+async fn tally_ranked_votes_leaves_out_exhausted_ballots() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 4).await;
+    let bob = harness.voter(realm, 3).await;
+    let carol = harness.voter(realm, 2).await;
+    let proposal = ranked_proposal(&mut harness, realm, &alice).await;
+
+    rank(&mut harness, proposal, &alice, vec![0]).await.unwrap();
+    rank(&mut harness, proposal, &bob, vec![1]).await.unwrap();
+    rank(&mut harness, proposal, &carol, vec![2]).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+
+    let voters = [&alice, &bob, &carol];
+    tally(&mut harness, proposal, &voters).await.unwrap();
+    tally(&mut harness, proposal, &voters).await.unwrap();
+
+    // Once Charlie is eliminated, Alpha holds 4 of the 7 remaining weight.
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.ranked_tally.unwrap().winner, Some(0));
+    assert_eq!(account.votes, vec![4, 3, 0]);
+}
+
+#[tokio::test]
+async fn tally_ranked_votes_without_ballots_has_no_winner() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = ranked_proposal(&mut harness, realm, &voter).await;
+
+    rank(&mut harness, proposal, &voter, vec![0, 1])
+        .await
+        .unwrap();
+    let instruction = withdraw_vote(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 0);
+    assert_eq!(account.ranked_tally.unwrap().ballots, 0);
+
+    harness.set_time(NOW + DAY + 1).await;
+    tally(&mut harness, proposal, &[]).await.unwrap();
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    let ranked_tally = account.ranked_tally.unwrap();
+    assert!(ranked_tally.complete);
+    assert_eq!(ranked_tally.winner, None);
+    assert_eq!(account.outcome, Some(ProposalOutcome::QuorumNotReached));
+}
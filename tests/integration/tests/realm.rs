@@ -0,0 +1,169 @@
+use solana_sdk::signature::Signer;
+use voting_dao::{ProposalError, Realm, MAX_DELETION_DELAY};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn create_realm_stores_configuration() {
+    let mut harness = Harness::start().await;
+    let instruction = create_realm(
+        &harness.payer(),
+        &harness.mint.pubkey(),
+        "dao",
+        10,
+        66,
+        180 * DAY,
+        3 * DAY,
+        DAY,
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    let realm: Realm = harness
+        .account(realm_address(&harness.payer(), "dao"))
+        .await;
+    assert_eq!(realm.authority, harness.payer());
+    assert_eq!(realm.name, "dao");
+    assert_eq!(realm.governing_mint, harness.mint.pubkey());
+    assert_eq!(realm.quorum, 10);
+    assert_eq!(realm.approval_threshold, 66);
+    assert_eq!(realm.proposal_count, 0);
+    assert_eq!(realm.deletion_delay, 180 * DAY);
+    assert_eq!(realm.min_voting_time, 3 * DAY);
+    assert_eq!(realm.hold_up_time, DAY);
+}
+
+#[tokio::test]
+async fn create_realm_name_is_scoped_to_its_creator() {
+    let mut harness = Harness::start().await;
+    let squatter = harness.member(0).await;
+
+    // A realm named "dao" by someone else does not take the name from the payer.
+    let instruction = create_realm(
+        &squatter.pubkey(),
+        &harness.mint.pubkey(),
+        "dao",
+        0,
+        50,
+        0,
+        0,
+        0,
+    );
+    harness
+        .process(&[instruction], &[&squatter.keypair])
+        .await
+        .unwrap();
+    let realm = harness.create_realm("dao").await;
+
+    assert_ne!(realm, realm_address(&squatter.pubkey(), "dao"));
+    let account: Realm = harness.account(realm).await;
+    assert_eq!(account.authority, harness.payer());
+}
+
+#[tokio::test]
+async fn create_realm_rejects_empty_name() {
+    let mut harness = Harness::start().await;
+    let instruction = create_realm(&harness.payer(), &harness.mint.pubkey(), "", 0, 50, 0, 0, 0);
+
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidRealmName);
+}
+
+#[tokio::test]
+async fn create_realm_rejects_invalid_threshold() {
+    let mut harness = Harness::start().await;
+    let instruction = create_realm(
+        &harness.payer(),
+        &harness.mint.pubkey(),
+        "dao",
+        0,
+        100,
+        0,
+        0,
+        0,
+    );
+
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidThreshold);
+}
+
+#[tokio::test]
+async fn update_realm_changes_rules_and_authority() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let admin = harness.member(0).await;
+
+    let instruction = update_realm(
+        &harness.payer(),
+        &realm,
+        &admin.pubkey(),
+        5,
+        75,
+        0,
+        DAY,
+        2 * DAY,
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    let account: Realm = harness.account(realm).await;
+    assert_eq!(account.authority, admin.pubkey());
+    assert_eq!(account.quorum, 5);
+    assert_eq!(account.approval_threshold, 75);
+    assert_eq!(account.deletion_delay, 0);
+    assert_eq!(account.min_voting_time, DAY);
+    assert_eq!(account.hold_up_time, 2 * DAY);
+}
+
+#[tokio::test]
+async fn update_realm_requires_authority() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let intruder = harness.member(0).await;
+
+    let instruction = update_realm(
+        &intruder.pubkey(),
+        &realm,
+        &intruder.pubkey(),
+        0,
+        50,
+        0,
+        0,
+        0,
+    );
+    let result = harness.process(&[instruction], &[&intruder.keypair]).await;
+    assert_error(result, ProposalError::NotRealmAuthority);
+}
+
+#[tokio::test]
+async fn update_realm_rejects_invalid_threshold() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+
+    let instruction = update_realm(&harness.payer(), &realm, &harness.payer(), 0, 49, 0, 0, 0);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidThreshold);
+}
+
+#[tokio::test]
+async fn realm_rejects_deletion_delay_above_maximum() {
+    let mut harness = Harness::start().await;
+    let instruction = create_realm(
+        &harness.payer(),
+        &harness.mint.pubkey(),
+        "dao",
+        0,
+        50,
+        MAX_DELETION_DELAY + 1,
+        0,
+        0,
+    );
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidDeletionDelay);
+
+    let realm = harness.create_realm("dao").await;
+    let payer = harness.payer();
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, MAX_DELETION_DELAY + 1, 0, 0);
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidDeletionDelay);
+
+    let instruction = update_realm(&payer, &realm, &payer, 0, 50, MAX_DELETION_DELAY, 0, 0);
+    harness.process(&[instruction], &[]).await.unwrap();
+}
@@ -0,0 +1,190 @@
+use voting_dao::{ProposalError, VotingType};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn close_vote_receipt_refunds_voter_after_end() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let receipt = vote_address(&proposal, &voter.pubkey());
+    let instruction = close_vote_receipt(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&voter.keypair])
+        .await;
+    assert_error(result, ProposalError::VoteNotEnded);
+
+    harness.set_time(NOW + DAY + 1).await;
+    let rent = harness.lamports(receipt).await;
+    let balance = harness.lamports(voter.pubkey()).await;
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    assert!(!harness.exists(receipt).await);
+    assert_eq!(harness.lamports(voter.pubkey()).await, balance + rent);
+}
+
+#[tokio::test]
+async fn close_vote_receipt_after_cancellation() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = cancel_proposal(&proposal, &realm, &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let instruction = close_vote_receipt(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+    assert!(
+        !harness
+            .exists(vote_address(&proposal, &voter.pubkey()))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn close_vote_receipt_waits_for_ranked_tally() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let params = ProposalParams {
+        voting_type: VotingType::Ranked,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, params)
+        .await
+        .unwrap();
+    let instruction = cast_ranked_vote(&proposal, &voter.pubkey(), &realm, vec![1]);
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY + 1).await;
+    let instruction = close_vote_receipt(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&voter.keypair])
+        .await;
+    assert_error(result, ProposalError::TallyNotComplete);
+
+    let receipts = [vote_address(&proposal, &voter.pubkey())];
+    let tally = tally_ranked_votes(&proposal, &harness.payer(), &receipts);
+    harness.process(&[tally], &[]).await.unwrap();
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn close_vote_receipts_refunds_voters_of_deleted_proposal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let alice = harness.voter(realm, 3).await;
+    let bob = harness.voter(realm, 2).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &alice, 0).await.unwrap();
+    harness.cast_vote(proposal, &bob, 1).await.unwrap();
+
+    harness.set_time(NOW + 31 * DAY).await;
+    let choices = [
+        choice_address(&proposal, "Pour"),
+        choice_address(&proposal, "Contre"),
+    ];
+    let instruction = delete_proposal(&proposal, &realm, &creator.pubkey(), &choices);
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+
+    let voters = [alice.pubkey(), bob.pubkey()];
+    let mut expected = Vec::new();
+    for voter in voters {
+        let rent = harness.lamports(vote_address(&proposal, &voter)).await;
+        expected.push(harness.lamports(voter).await + rent);
+    }
+
+    let receipts = voters.map(|voter| (voter, voter));
+    let instruction = close_vote_receipts(&proposal, &realm, &harness.payer(), &receipts);
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    for (voter, expected) in voters.into_iter().zip(expected) {
+        assert!(!harness.exists(vote_address(&proposal, &voter)).await);
+        assert_eq!(harness.lamports(voter).await, expected);
+    }
+}
+
+#[tokio::test]
+async fn close_vote_receipts_rejects_other_payer() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 3).await;
+    let bob = harness.voter(realm, 2).await;
+    let proposal = harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let other = harness
+        .create_proposal(realm, &alice.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &alice, 0).await.unwrap();
+    harness.set_time(NOW + DAY + 1).await;
+
+    let mut instruction = close_vote_receipts(
+        &proposal,
+        &realm,
+        &harness.payer(),
+        &[(alice.pubkey(), alice.pubkey())],
+    );
+    let payer = instruction.accounts.len() - 2;
+    instruction.accounts[payer].pubkey = bob.pubkey();
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidVoteReceipt);
+
+    // The receipt cannot release the deposit of another voter.
+    let mut instruction = close_vote_receipts(
+        &proposal,
+        &realm,
+        &harness.payer(),
+        &[(alice.pubkey(), alice.pubkey())],
+    );
+    let record = instruction.accounts.len() - 1;
+    instruction.accounts[record].pubkey = voter_record_address(&realm, &bob.pubkey());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidVoterRecord);
+
+    // A receipt of another proposal is rejected.
+    let mut instruction = close_vote_receipts(
+        &other,
+        &realm,
+        &harness.payer(),
+        &[(alice.pubkey(), alice.pubkey())],
+    );
+    instruction.accounts[3].pubkey = vote_address(&proposal, &alice.pubkey());
+    let result = harness.process(&[instruction], &[]).await;
+    assert_error(result, ProposalError::InvalidVoteReceipt);
+}
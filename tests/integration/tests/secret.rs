@@ -0,0 +1,197 @@
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::BanksClientError;
+use voting_dao::{commitment_hash, Proposal, ProposalError, ProposalOutcome, Voting};
+use voting_dao_tests::*;
+
+const SALT: [u8; 32] = [42; 32];
+
+/// Creates a proposal using secret ballots, revealed during the day following its end.
+async fn secret_proposal(harness: &mut Harness, realm: Pubkey, creator: &Member) -> Pubkey {
+    let params = ProposalParams {
+        reveal_period: Some(DAY),
+        ..ProposalParams::default()
+    };
+    harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await
+        .unwrap()
+}
+
+async fn commit(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voter: &Member,
+    choice: u8,
+) -> Result<(), BanksClientError> {
+    let commitment = commitment_hash(&voter.pubkey(), choice, &SALT);
+    let realm = harness.realm_of(proposal).await;
+    let instruction = commit_vote(&proposal, &voter.pubkey(), &realm, commitment);
+    harness.process(&[instruction], &[&voter.keypair]).await
+}
+
+async fn reveal(
+    harness: &mut Harness,
+    proposal: Pubkey,
+    voter: &Member,
+    choice: u8,
+    salt: [u8; 32],
+) -> Result<(), BanksClientError> {
+    let instruction = reveal_vote(&proposal, &voter.pubkey(), choice, salt);
+    harness.process(&[instruction], &[&voter.keypair]).await
+}
+
+#[tokio::test]
+async fn create_proposal_sets_reveal_end() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let proposal = secret_proposal(&mut harness, realm, &creator).await;
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.reveal_end, Some(NOW + 2 * DAY));
+    assert_eq!(account.closing_time(), NOW + 2 * DAY);
+}
+
+#[tokio::test]
+async fn create_proposal_rejects_empty_reveal_period() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+
+    let params = ProposalParams {
+        reveal_period: Some(0),
+        ..ProposalParams::default()
+    };
+    let result = harness
+        .create_proposal(realm, &creator.keypair, params)
+        .await;
+    assert_error(result.map(|_| ()), ProposalError::InvalidRevealPeriod);
+}
+
+#[tokio::test]
+async fn commit_vote_hides_choice_until_reveal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = secret_proposal(&mut harness, realm, &voter).await;
+
+    commit(&mut harness, proposal, &voter, 1).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert!(account.votes.iter().all(|count| *count == 0));
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choice, None);
+    assert_eq!(receipt.weight, 5);
+    assert_eq!(
+        receipt.commitment,
+        Some(commitment_hash(&voter.pubkey(), 1, &SALT))
+    );
+
+    harness.set_time(NOW + DAY).await;
+    reveal(&mut harness, proposal, &voter, 1, SALT)
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[1], 5);
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choice, Some(1));
+    assert_eq!(receipt.commitment, None);
+}
+
+#[tokio::test]
+async fn vote_instructions_require_matching_mode() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let public = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    let secret = secret_proposal(&mut harness, realm, &voter).await;
+
+    let result = harness.cast_vote(secret, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    let result = commit(&mut harness, public, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+
+    commit(&mut harness, secret, &voter, 0).await.unwrap();
+    let instruction = change_vote(&secret, &voter.pubkey(), 1, &[]);
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::InvalidVotingMode);
+}
+
+#[tokio::test]
+async fn reveal_vote_only_during_reveal_window() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = secret_proposal(&mut harness, realm, &voter).await;
+    commit(&mut harness, proposal, &voter, 0).await.unwrap();
+
+    let result = reveal(&mut harness, proposal, &voter, 0, SALT).await;
+    assert_error(result, ProposalError::RevealNotOpen);
+
+    harness.set_time(NOW + 2 * DAY + 1).await;
+    let result = reveal(&mut harness, proposal, &voter, 0, SALT).await;
+    assert_error(result, ProposalError::RevealNotOpen);
+}
+
+#[tokio::test]
+async fn reveal_vote_rejects_wrong_preimage_and_second_reveal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 5).await;
+    let proposal = secret_proposal(&mut harness, realm, &voter).await;
+    commit(&mut harness, proposal, &voter, 0).await.unwrap();
+    harness.set_time(NOW + DAY).await;
+
+    let result = reveal(&mut harness, proposal, &voter, 1, SALT).await;
+    assert_error(result, ProposalError::InvalidReveal);
+
+    let result = reveal(&mut harness, proposal, &voter, 0, [0; 32]).await;
+    assert_error(result, ProposalError::InvalidReveal);
+
+    reveal(&mut harness, proposal, &voter, 0, SALT)
+        .await
+        .unwrap();
+    let result = reveal(&mut harness, proposal, &voter, 0, SALT).await;
+    assert_error(result, ProposalError::InvalidReveal);
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 5);
+}
+
+#[tokio::test]
+async fn finalize_proposal_leaves_out_unrevealed_commitments() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let alice = harness.voter(realm, 2).await;
+    let bob = harness.voter(realm, 10).await;
+    let proposal = secret_proposal(&mut harness, realm, &alice).await;
+
+    commit(&mut harness, proposal, &alice, 0).await.unwrap();
+    commit(&mut harness, proposal, &bob, 1).await.unwrap();
+
+    harness.set_time(NOW + DAY).await;
+    reveal(&mut harness, proposal, &alice, 0, SALT)
+        .await
+        .unwrap();
+
+    let result = harness.finalize_proposal(proposal).await;
+    assert_error(result, ProposalError::VoteNotEnded);
+
+    harness.set_time(NOW + 2 * DAY + 1).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 2);
+    assert_eq!(account.votes[1], 0);
+    assert_eq!(account.outcome, Some(ProposalOutcome::Succeeded));
+}
@@ -0,0 +1,238 @@
+use anchor_spl::token::TokenAccount;
+use solana_sdk::signature::Signer;
+use voting_dao::{ProposalError, VoterRecord};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn deposit_governing_tokens_moves_tokens_to_vault() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let member = harness.member(30).await;
+
+    harness.deposit(realm, &member, 10).await.unwrap();
+    harness.deposit(realm, &member, 20).await.unwrap();
+
+    let record: VoterRecord = harness
+        .account(voter_record_address(&realm, &member.pubkey()))
+        .await;
+    assert_eq!(record.realm, realm);
+    assert_eq!(record.owner, member.pubkey());
+    assert_eq!(record.deposited, 30);
+    assert_eq!(record.active_votes, 0);
+
+    let vault: TokenAccount = harness.account(vault_address(&realm)).await;
+    assert_eq!(vault.amount, 30);
+    assert_eq!(vault.owner, vault_address(&realm));
+    let wallet: TokenAccount = harness.account(member.token_account).await;
+    assert_eq!(wallet.amount, 0);
+}
+
+#[tokio::test]
+async fn deposit_governing_tokens_creates_prefunded_vault() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let member = harness.member(10).await;
+
+    // Lamports sent to the vault address before the first deposit must not block the realm.
+    harness.transfer(vault_address(&realm), 1_000_000).await;
+    harness.deposit(realm, &member, 10).await.unwrap();
+
+    let vault: TokenAccount = harness.account(vault_address(&realm)).await;
+    assert_eq!(vault.amount, 10);
+    assert_eq!(vault.mint, harness.mint.pubkey());
+    assert_eq!(vault.owner, vault_address(&realm));
+}
+
+#[tokio::test]
+async fn deposit_governing_tokens_rejects_token_account_of_another_owner() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let member = harness.member(0).await;
+    let other = harness.member(100).await;
+
+    let instruction = deposit_governing_tokens(
+        &realm,
+        &harness.mint.pubkey(),
+        &member.pubkey(),
+        &other.token_account,
+        100,
+    );
+    let result = harness.process(&[instruction], &[&member.keypair]).await;
+    assert_error(result, ProposalError::InvalidTokenAccount);
+}
+
+#[tokio::test]
+async fn withdraw_governing_tokens_waits_for_vote_receipts_to_close() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 10).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    // The tokens counted in the vote cannot be moved to another wallet to vote again.
+    let instruction = withdraw_governing_tokens(&realm, &voter.pubkey(), &voter.token_account, 10);
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&voter.keypair])
+        .await;
+    assert_error(result, ProposalError::DepositLocked);
+
+    // The receipt still counts the deposit once the vote has ended, until it is closed.
+    harness.set_time(NOW + DAY + 1).await;
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::DepositLocked);
+    let record: VoterRecord = harness
+        .account(voter_record_address(&realm, &voter.pubkey()))
+        .await;
+    assert_eq!(record.active_votes, 1);
+
+    let instruction = close_vote_receipt(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+    let instruction = withdraw_governing_tokens(&realm, &voter.pubkey(), &voter.token_account, 11);
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::InsufficientDeposit);
+
+    let instruction = withdraw_governing_tokens(&realm, &voter.pubkey(), &voter.token_account, 4);
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let record: VoterRecord = harness
+        .account(voter_record_address(&realm, &voter.pubkey()))
+        .await;
+    assert_eq!(record.deposited, 6);
+    assert_eq!(record.active_votes, 0);
+    let wallet: TokenAccount = harness.account(voter.token_account).await;
+    assert_eq!(wallet.amount, 4);
+    let vault: TokenAccount = harness.account(vault_address(&realm)).await;
+    assert_eq!(vault.amount, 6);
+}
+
+#[tokio::test]
+async fn withdraw_governing_tokens_after_withdrawn_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 10).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    // Withdrawing the vote removes its weight, so the tokens can leave right away.
+    let instruction = withdraw_vote(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+    let instruction = withdraw_governing_tokens(&realm, &voter.pubkey(), &voter.token_account, 10);
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let wallet: TokenAccount = harness.account(voter.token_account).await;
+    assert_eq!(wallet.amount, 10);
+}
+
+#[tokio::test]
+async fn withdraw_governing_tokens_after_cancelled_proposal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let creator = harness.member(0).await;
+    let voter = harness.voter(realm, 10).await;
+    let proposal = harness
+        .create_proposal(realm, &creator.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = cancel_proposal(&proposal, &realm, &creator.pubkey());
+    harness
+        .process(&[instruction], &[&creator.keypair])
+        .await
+        .unwrap();
+
+    // The receipt of a cancelled proposal can be closed before its end date, which releases the deposit.
+    let instruction = withdraw_governing_tokens(&realm, &voter.pubkey(), &voter.token_account, 10);
+    let result = harness
+        .process(std::slice::from_ref(&instruction), &[&voter.keypair])
+        .await;
+    assert_error(result, ProposalError::DepositLocked);
+    let close = close_vote_receipt(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness.process(&[close], &[&voter.keypair]).await.unwrap();
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let wallet: TokenAccount = harness.account(voter.token_account).await;
+    assert_eq!(wallet.amount, 10);
+}
+
+#[tokio::test]
+async fn delegated_vote_locks_deposit_of_delegator() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &owner.pubkey()),
+        vote_address(&proposal, &owner.pubkey()),
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    harness
+        .process(&[instruction], &[&representative.keypair])
+        .await
+        .unwrap();
+
+    let instruction = withdraw_governing_tokens(&realm, &owner.pubkey(), &owner.token_account, 10);
+    let result = harness.process(&[instruction], &[&owner.keypair]).await;
+    assert_error(result, ProposalError::DepositLocked);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_record_of_another_delegator() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let owner = harness.voter(realm, 10).await;
+    let whale = harness.voter(realm, 1_000).await;
+    let representative = harness.voter(realm, 5).await;
+    let proposal = harness
+        .create_proposal(realm, &owner.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = delegate(&realm, &owner.pubkey(), &representative.pubkey());
+    harness
+        .process(&[instruction], &[&owner.keypair])
+        .await
+        .unwrap();
+    let delegators = [(
+        delegation_address(&realm, &owner.pubkey()),
+        voter_record_address(&realm, &whale.pubkey()),
+        vote_address(&proposal, &owner.pubkey()),
+    )];
+    let instruction = cast_vote(&proposal, &representative.pubkey(), &realm, 0, &delegators);
+    let result = harness
+        .process(&[instruction], &[&representative.keypair])
+        .await;
+    assert_error(result, ProposalError::InvalidVoterRecord);
+}
@@ -0,0 +1,261 @@
+use voting_dao::{Proposal, ProposalError, ProposalState, Voting};
+use voting_dao_tests::*;
+
+#[tokio::test]
+async fn cast_vote_adds_deposit_and_fills_receipt() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 40).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + 60).await;
+    harness.cast_vote(proposal, &voter, 1).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 0);
+    assert_eq!(account.votes[1], 40);
+
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choice, Some(1));
+    assert_eq!(receipt.voter, voter.pubkey());
+    assert_eq!(receipt.proposal, proposal);
+    assert_eq!(receipt.weight, 40);
+    assert_eq!(receipt.timestamp, NOW + 60);
+}
+
+#[tokio::test]
+async fn cast_vote_moves_draft_proposal_to_voting() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, params)
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY).await;
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.state, ProposalState::Voting);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_vote_before_start() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let params = ProposalParams {
+        date_start: NOW + DAY,
+        date_end: NOW + 2 * DAY,
+        ..ProposalParams::default()
+    };
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, params)
+        .await
+        .unwrap();
+
+    let result = harness.cast_vote(proposal, &voter, 0).await;
+    assert_error(result, ProposalError::VoteNotOpen);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_vote_after_end() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + DAY).await;
+    let result = harness.cast_vote(proposal, &voter, 0).await;
+    assert_error(result, ProposalError::VoteClosed);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_finalized_proposal() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.set_time(NOW + 2 * DAY).await;
+    harness.finalize_proposal(proposal).await.unwrap();
+
+    let result = harness.cast_vote(proposal, &voter, 0).await;
+    assert_error(result, ProposalError::InvalidProposalState);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_unknown_choice() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let result = harness.cast_vote(proposal, &voter, 2).await;
+    assert_error(result, ProposalError::InvalidChoice);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_deposit_in_another_realm() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let other = harness.create_realm("other").await;
+    let voter = harness.voter(other, 100).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let instruction = cast_vote(&proposal, &voter.pubkey(), &other, 0, &[]);
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert!(result.is_err());
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 0);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_voter_without_tokens() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 0).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    let result = harness.cast_vote(proposal, &voter, 0).await;
+    assert_error(result, ProposalError::NoVotingWeight);
+}
+
+#[tokio::test]
+async fn cast_vote_rejects_second_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 1).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+    assert!(harness.cast_vote(proposal, &voter, 0).await.is_err());
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 1);
+}
+
+#[tokio::test]
+async fn change_vote_moves_weight_to_new_choice() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 7).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = change_vote(&proposal, &voter.pubkey(), 1, &[]);
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 0);
+    assert_eq!(account.votes[1], 7);
+
+    let receipt: Voting = harness
+        .account(vote_address(&proposal, &voter.pubkey()))
+        .await;
+    assert_eq!(receipt.choice, Some(1));
+}
+
+#[tokio::test]
+async fn change_vote_rejects_unknown_choice_and_closed_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 7).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = change_vote(&proposal, &voter.pubkey(), 2, &[]);
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::InvalidChoice);
+
+    harness.set_time(NOW + DAY).await;
+    let instruction = change_vote(&proposal, &voter.pubkey(), 1, &[]);
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::VoteClosed);
+}
+
+#[tokio::test]
+async fn withdraw_vote_removes_weight_and_allows_new_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 7).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    let instruction = withdraw_vote(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    harness
+        .process(&[instruction], &[&voter.keypair])
+        .await
+        .unwrap();
+
+    assert!(
+        !harness
+            .exists(vote_address(&proposal, &voter.pubkey()))
+            .await
+    );
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[0], 0);
+
+    harness.cast_vote(proposal, &voter, 1).await.unwrap();
+    let account: Proposal = harness.account(proposal).await;
+    assert_eq!(account.votes[1], 7);
+}
+
+#[tokio::test]
+async fn withdraw_vote_rejects_closed_vote() {
+    let mut harness = Harness::start().await;
+    let realm = harness.create_realm("dao").await;
+    let voter = harness.voter(realm, 7).await;
+    let proposal = harness
+        .create_proposal(realm, &voter.keypair, ProposalParams::default())
+        .await
+        .unwrap();
+    harness.cast_vote(proposal, &voter, 0).await.unwrap();
+
+    harness.set_time(NOW + DAY).await;
+    let instruction = withdraw_vote(&proposal, &realm, &voter.pubkey(), &voter.pubkey());
+    let result = harness.process(&[instruction], &[&voter.keypair]).await;
+    assert_error(result, ProposalError::VoteClosed);
+}